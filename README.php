@@ -26,6 +26,265 @@ in typescript.
 - Run your test-suite with:
   `str $FILE_NAME`
 
+`str` can be run from any subdirectory of your project. It looks for the
+config file, `tsconfig.json` and `node_modules` in the project root (the
+nearest directory with a `package.json`) and reports test files relative
+to it. The tests themselves run in the directory you started `str` in.
+
+In yarn or npm workspaces, `str` can be run from the workspace root or
+from any package directory. Imports of other workspace packages are
+bundled from source (so they can be written in typescript), and other
+dependencies are loaded from the `node_modules` directories of the
+workspace.
+
+## Skipped and planned tests
+
+`it.todo("name")` registers a test that isn't written yet, and is reported
+as `TODO`. `it.skipIf(condition)("name", test)` skips a test if
+`condition` is true, `it.runIf(condition)` only runs it if `condition` is
+true. Tests can also skip themselves at runtime with `ctx.skip(reason)`
+(see below). Skipped tests are reported as `SKIPPED`, and counted
+separately from tests that are ignored with `xit`.
+
+`it.failing("name", test)` registers a test for a known bug that is expected
+to fail. It's reported as `XFAIL` as long as it fails, and makes the test
+run fail once it passes, so you remember to turn it into a normal test.
+
+```typescript
+it.skipIf(process.platform === "win32")("uses symlinks", () => {});
+it.todo("handles unicode");
+```
+
+## Test context
+
+Tests and `beforeEach`/`afterEach` hooks receive a context object with:
+
+- `name`, `path` and `fullName`: the test's name, the test file and
+  `describe` blocks it's in, and both joined as in the output
+- `signal`: an `AbortSignal` that is aborted when the test times out
+- `tempDir`: a temporary directory that is removed after the test
+- `skip(reason)`: stops the test and reports it as skipped
+- `onCleanup(fn)`: runs `fn` after the test, even if the test fails
+- `note(key, value)`: reports the given value below the test's result and
+  in the JSON and JUnit reports
+
+```typescript
+import { it } from "str";
+
+it("downloads a file", async (ctx) => {
+  ctx.onCleanup(() => server.close());
+  await download(url, ctx.tempDir, { signal: ctx.signal });
+});
+```
+
+## Parameterized tests
+
+`it.each` and `describe.each` register one test (or `describe` block) for
+every row of a table. Rows that are arrays are passed as separate arguments.
+Test names can contain `%s`, `%d`, `%j` or `%p` (replaced with the row's
+arguments in order), `%#` (the row's index) and `$field` (a field of a row
+that is an object). The test context is passed after the row's arguments:
+
+```typescript
+import { it, assertEq } from "str";
+
+it.each([
+  [1, 2, 3],
+  [2, 3, 5],
+])("adds %d and %d", (a, b, sum) => {
+  assertEq(a + b, sum);
+});
+
+it.each([{ input: "foo", length: 3 }])(
+  "$input has length $length",
+  (row, ctx) => {
+    ctx.note("input", row.input);
+    assertEq(row.input.length, row.length);
+  }
+);
+```
+
+## Property-based testing
+
+`property` registers a test that checks a predicate for many (by default
+100) randomly generated inputs. The predicate fails by returning `false` or
+by throwing. Failing inputs are shrunk to a minimal counterexample, which is
+reported together with the random seed:
+
+```typescript
+import { property, gen } from "str";
+
+property("reverse keeps the length", [gen.array(gen.int())], (xs) => {
+  return [...xs].reverse().length === xs.length;
+});
+```
+
+`gen` contains generators for `int(min, max)`, `string()`, `array(element)`,
+`record({ field: generator })` and `oneOf(...generators)`. Generators can be
+transformed with `.map(f)` and `.filter(predicate)`. The number of runs and
+the seed can be set with e.g. `{ runs: 1000, seed: 42 }` as the last
+argument to `property`, or the seed for all properties with `--seed`.
+
+## Mocks and spies
+
+`mockFn()` creates a function that records its calls (`.calls`) and their
+results (`.results`). Its behavior can be set with `mockReturnValue`,
+`mockResolvedValue` and `mockImplementation`. `spyOn(object, "method")`
+replaces a method with such a mock that calls the original method by
+default. Spies that are created during a test (or its `beforeEach` hooks)
+are restored after the test.
+
+```typescript
+import { it, mockFn, spyOn, assertCalledWith, assertCalledTimes } from "str";
+
+it("works", () => {
+  const callback = mockFn();
+  [1, 2].forEach((x) => callback(x));
+  assertCalledTimes(callback, 2);
+  assertCalledWith(callback, 2);
+  spyOn(Math, "random").mockReturnValue(0.5);
+});
+```
+
+`mockModule` replaces a module (a local one or one from `node_modules`)
+for the test file that calls it and everything that test file imports:
+
+```typescript
+import { it, mockFn, mockModule } from "str";
+import { fetchData } from "./client";
+
+mockModule("./http", () => ({ get: mockFn().mockReturnValue("fake") }));
+```
+
+The factory is run before the test file, so it can only use `mockFn`,
+`spyOn` and global variables. Using imports or variables of the test file,
+or passing the module as anything but a string literal, is an error.
+
+## Fake timers
+
+`useFakeTimers()` replaces `setTimeout`, `setInterval` and `Date` with fakes
+that only move forward when you tell them to: `advanceTimersBy(ms)` runs the
+timers that are due within the next `ms` milliseconds, `runAllTimers()` runs
+all pending timers and `setSystemTime(date)` sets the current date. Fake
+timers are restored after every test, or explicitly with `useRealTimers()`.
+Test timeouts are not affected by fake timers.
+
+```typescript
+import { it, useFakeTimers, advanceTimersBy } from "str";
+
+it("retries after an hour", async () => {
+  useFakeTimers();
+  const retried = retryLater(60 * 60 * 1000);
+  advanceTimersBy(60 * 60 * 1000);
+  await retried;
+});
+```
+
+## Configuration
+
+`str` reads its configuration from `str.config.ts`, `str.config.json` or
+the `"str"` field in `package.json` (in that order). Command line flags
+override settings from the config file. Example:
+
+```json
+{
+  "testFiles": ["src/**/*.test.ts"],
+  "ignore": ["src/fixtures/**"],
+  "timeout": 5000,
+  "env": { "NODE_ENV": "test" },
+  "esbuild": { "target": "es2020" }
+}
+```
+
+- `testFiles`: globs for test files that are run when no test files are
+  given on the command line (`**` matches any number of directories)
+- `ignore`, `--ignore <glob>`: globs for files to exclude from `testFiles`
+- `timeout`, `--timeout <ms>`: fail tests that take longer than that
+- `env`, `--env KEY=VALUE`: environment variables for the tests
+- `envFiles`, `--env-file <file>`: read environment variables for the
+  tests from `.env` files. They take precedence over the inherited
+  environment, but not over `env`.
+- `esbuild.target`, `--target <target>`: e.g. `es2020` or `esnext`
+- `esbuild.format`, `--format <format>`: `cjs` or `esm`. Defaults to
+  `esm` if your `package.json` has `"type": "module"`, otherwise to `cjs`.
+  In `esm` mode the tests are run as a native ES module (`main.mjs`),
+  which allows top-level `await`, and imports from `node_modules` are
+  resolved with node's ES module resolution, which ignores `NODE_PATH`.
+  For that the bundle has to live inside your project, so a `cacheDir` or
+  `--keep-bundle` directory outside of it is rejected. In a workspace,
+  dependencies that are only installed in another package's
+  `node_modules` can't be imported.
+- `esbuild.jsxFactory`, `esbuild.jsxFragment`
+- `esbuild.define`, `--define KEY=VALUE`: replace global identifiers with
+  constant expressions at compile time, e.g. `--define DEBUG=false`
+- `esbuild.loader`, `--loader .EXT=LOADER`: how to import files with the
+  given extension, e.g. `--loader .graphql=text`. Supported loaders are
+  `js`, `jsx`, `ts`, `tsx`, `json`, `text`, `base64`, `binary` (a
+  `Uint8Array`), `dataurl` and `file` (the absolute path of the file).
+  `.json` and `.txt` files are imported as `json` and `text` by default.
+- `esbuild.tsconfig`, `--tsconfig <file>`: the tsconfig file to use.
+  Defaults to `tsconfig.json`. `str` honors its `paths`, `baseUrl`,
+  `jsxFactory`, `jsxFragmentFactory`, `jsx` (including the automatic
+  runtime with `react-jsx` and `react-jsxdev`), `jsxImportSource` and
+  `target` settings. Imports that
+  resolve through `paths` or `baseUrl` are bundled, other non-relative
+  imports are loaded from `node_modules` at runtime.
+- `setupFiles`, `--setup <file>`: modules that are imported before any
+  test file, e.g. for polyfills
+- `globalSetup`, `--global-setup <file>`: a module whose default export is
+  run once before all tests. Its (serializable) return value is available
+  to tests through `globalSetupData()`.
+- `globalTeardown`, `--global-teardown <file>`: a module whose default
+  export is run once after all tests. It receives the return value of the
+  global setup.
+- `capture`, `--capture`, `--no-capture`: hide output (`console.*`,
+  `process.stdout` and `process.stderr`) of tests and their hooks unless
+  the test or hook fails
+- `slow`, `--slow <ms>`: highlight tests that take at least this long and
+  list the slowest of them after the summary
+- `coverage`, `--coverage`: collect line coverage of your source files
+  (excluding test files and `node_modules`). A summary table is printed
+  after the tests, and `coverage/lcov.info` and
+  `coverage/coverage-summary.json` are written.
+- `coverageThreshold`, `--coverage-threshold <percent>`: collect coverage
+  and fail the run if less than the given percentage of lines is covered
+- `typecheck`, `--typecheck`, `--no-typecheck`: type-check the test files
+  (and everything they import) with your project's `tsc` before running
+  them. Requires `typescript` to be installed in your project.
+- `moduleMocks`: replace modules with other files in all test files, e.g.
+  `{ "./src/http": "./test/fake-http.ts" }`. The fake modules themselves
+  can still import the original modules.
+- `reporters`, `--json-report <file>`, `--junit-report <file>`: write
+  reports of the test results to the given files, e.g.
+  `{ "json": "reports/str.json", "junit": "reports/junit.xml" }`. The JSON
+  report contains a summary and the status, duration and failure message
+  of every test, the JUnit report can be read by most CI servers.
+- `parallelism`, `--parallelism <n>`: run the test files in up to `n`
+  worker processes. Every worker imports the setup files and its share
+  of the test files, the global setup and teardown still run only once.
+  The output of each worker is printed once the worker is done. If a
+  worker crashes, each of its test files counts as one failed test.
+  Defaults to running all tests in a single process.
+- `cacheDir`: where to cache bundles between runs. Defaults to
+  `node_modules/.cache/str`. Bundles are reused as long as none of the
+  bundled files and none of the `tsconfig.json`, `jsconfig.json` and
+  `package.json` files that esbuild reads changed. Only the 20 most recently used bundles are kept.
+- `--no-cache`: don't use or write the bundle cache
+- `--config <file>`: use the given config file
+- `--filter <pattern>`: only run tests whose path (e.g.
+  `index.test.ts -> my app -> works`) contains the given pattern
+- `--seed <seed>`: use the given seed for property-based tests, e.g. to
+  reproduce a failure
+- `--inspect[=[host:]port]`, `--inspect-brk[=[host:]port]`: start the
+  node inspector, so you can debug your tests (with breakpoints in your
+  `.ts` files) in e.g. Chrome's DevTools. Timeouts are disabled while
+  inspecting. Combine with `--filter` to debug a single test.
+- `--keep-bundle <dir>`: write the bundled test code (`main.js`), its
+  source map and esbuild's metafile (`meta.json`) to the given directory
+  instead of a temporary one
+- `--print-runner`: print the generated runner code instead of running the
+  tests
+
 ## How to run the tests
 - Install [rust](https://www.rust-lang.org/).
 - Install [just](https://github.com/casey/just)
@@ -0,0 +1,471 @@
+package cli
+
+import (
+	"os"
+	"strconv"
+	"strings"
+)
+
+// flag describes a command line flag. `argument` is the name of the
+// flag's argument, or empty for flags that don't take an argument.
+// Optional arguments can only be given as `--name=value`. Arguments of
+// flags with `path` set are paths relative to the current directory.
+type flag struct {
+	name             string
+	argument         string
+	optionalArgument bool
+	path             bool
+	apply            func(config *Config, value string) error
+}
+
+var flags = []flag{
+	{
+		name:     "--timeout",
+		argument: "ms",
+		apply: func(config *Config, value string) error {
+			timeout, err := strconv.Atoi(value)
+			if err != nil {
+				return configErrorf("--timeout: not a number: %s", value)
+			}
+			config.Timeout = timeout
+			return nil
+		},
+	},
+	{
+		name:     "--slow",
+		argument: "ms",
+		apply: func(config *Config, value string) error {
+			slow, err := strconv.Atoi(value)
+			if err != nil {
+				return configErrorf("--slow: not a number: %s", value)
+			}
+			config.Slow = slow
+			return nil
+		},
+	},
+	{
+		name:     "--ignore",
+		argument: "glob",
+		apply: func(config *Config, value string) error {
+			config.Ignore = append(config.Ignore, value)
+			return nil
+		},
+	},
+	{
+		name:     "--env",
+		argument: "KEY=VALUE",
+		apply: func(config *Config, value string) error {
+			key, val, err := splitKeyValue("--env", value)
+			if err != nil {
+				return err
+			}
+			if config.Env == nil {
+				config.Env = map[string]string{}
+			}
+			config.Env[key] = val
+			return nil
+		},
+	},
+	{
+		name:     "--env-file",
+		argument: "file",
+		path:     true,
+		apply: func(config *Config, value string) error {
+			config.EnvFiles = append(config.EnvFiles, value)
+			return nil
+		},
+	},
+	{
+		name:     "--define",
+		argument: "KEY=VALUE",
+		apply: func(config *Config, value string) error {
+			key, val, err := splitKeyValue("--define", value)
+			if err != nil {
+				return err
+			}
+			if config.Esbuild.Define == nil {
+				config.Esbuild.Define = map[string]string{}
+			}
+			config.Esbuild.Define[key] = val
+			return nil
+		},
+	},
+	{
+		name:     "--loader",
+		argument: ".EXT=LOADER",
+		apply: func(config *Config, value string) error {
+			extension, loader, err := splitKeyValue("--loader", value)
+			if err != nil {
+				return err
+			}
+			if config.Esbuild.Loader == nil {
+				config.Esbuild.Loader = map[string]string{}
+			}
+			config.Esbuild.Loader[extension] = loader
+			return nil
+		},
+	},
+	{
+		name:     "--format",
+		argument: "format",
+		apply: func(config *Config, value string) error {
+			config.Esbuild.Format = value
+			return nil
+		},
+	},
+	{
+		name:     "--target",
+		argument: "target",
+		apply: func(config *Config, value string) error {
+			config.Esbuild.Target = value
+			return nil
+		},
+	},
+	{
+		name:     "--tsconfig",
+		argument: "file",
+		path:     true,
+		apply: func(config *Config, value string) error {
+			config.Esbuild.Tsconfig = value
+			return nil
+		},
+	},
+	{
+		name:     "--setup",
+		argument: "file",
+		path:     true,
+		apply: func(config *Config, value string) error {
+			config.SetupFiles = append(config.SetupFiles, value)
+			return nil
+		},
+	},
+	{
+		name:     "--global-setup",
+		argument: "file",
+		path:     true,
+		apply: func(config *Config, value string) error {
+			config.GlobalSetup = value
+			return nil
+		},
+	},
+	{
+		name:     "--global-teardown",
+		argument: "file",
+		path:     true,
+		apply: func(config *Config, value string) error {
+			config.GlobalTeardown = value
+			return nil
+		},
+	},
+	{
+		name: "--capture",
+		apply: func(config *Config, value string) error {
+			config.Capture = true
+			return nil
+		},
+	},
+	{
+		name: "--no-capture",
+		apply: func(config *Config, value string) error {
+			config.Capture = false
+			return nil
+		},
+	},
+	{
+		name: "--coverage",
+		apply: func(config *Config, value string) error {
+			config.Coverage = true
+			return nil
+		},
+	},
+	{
+		name:     "--coverage-threshold",
+		argument: "percent",
+		apply: func(config *Config, value string) error {
+			threshold, err := strconv.ParseFloat(value, 64)
+			if err != nil {
+				return configErrorf("--coverage-threshold: not a number: %s", value)
+			}
+			config.CoverageThreshold = threshold
+			return nil
+		},
+	},
+	{
+		name:     "--filter",
+		argument: "pattern",
+		apply: func(config *Config, value string) error {
+			config.Filter = value
+			return nil
+		},
+	},
+	{
+		name:     "--parallelism",
+		argument: "n",
+		apply: func(config *Config, value string) error {
+			parallelism, err := strconv.Atoi(value)
+			if err != nil {
+				return configErrorf("--parallelism: not a number: %s", value)
+			}
+			config.Parallelism = parallelism
+			return nil
+		},
+	},
+	{
+		name:     "--json-report",
+		argument: "file",
+		path:     true,
+		apply:    reporter("json"),
+	},
+	{
+		name:     "--junit-report",
+		argument: "file",
+		path:     true,
+		apply:    reporter("junit"),
+	},
+	{
+		name:     "--seed",
+		argument: "seed",
+		apply: func(config *Config, value string) error {
+			seed, err := strconv.ParseUint(value, 10, 32)
+			if err != nil {
+				return configErrorf("--seed: not a number: %s", value)
+			}
+			seed32 := uint32(seed)
+			config.Seed = &seed32
+			return nil
+		},
+	},
+	{
+		name:             "--inspect",
+		argument:         "[host:]port",
+		optionalArgument: true,
+		apply:            inspect("--inspect"),
+	},
+	{
+		name:             "--inspect-brk",
+		argument:         "[host:]port",
+		optionalArgument: true,
+		apply:            inspect("--inspect-brk"),
+	},
+	{
+		name:     "--keep-bundle",
+		argument: "dir",
+		path:     true,
+		apply: func(config *Config, value string) error {
+			config.KeepBundle = value
+			return nil
+		},
+	},
+	{
+		name: "--typecheck",
+		apply: func(config *Config, value string) error {
+			config.Typecheck = true
+			return nil
+		},
+	},
+	{
+		name: "--no-typecheck",
+		apply: func(config *Config, value string) error {
+			config.Typecheck = false
+			return nil
+		},
+	},
+	{
+		name: "--no-cache",
+		apply: func(config *Config, value string) error {
+			config.NoCache = true
+			return nil
+		},
+	},
+	{
+		name: "--print-runner",
+		apply: func(config *Config, value string) error {
+			config.PrintRunner = true
+			return nil
+		},
+	},
+}
+
+// reporter returns a flag that writes a report of the given kind to a file.
+func reporter(kind string) func(config *Config, value string) error {
+	return func(config *Config, value string) error {
+		if config.Reporters == nil {
+			config.Reporters = map[string]string{}
+		}
+		config.Reporters[kind] = value
+		return nil
+	}
+}
+
+// inspect returns a flag that is passed on to node.
+func inspect(nodeFlag string) func(config *Config, value string) error {
+	return func(config *Config, value string) error {
+		config.Inspect = nodeFlag
+		if value != "" {
+			config.Inspect += "=" + value
+		}
+		return nil
+	}
+}
+
+type args struct {
+	configFile string
+	testFiles  []string
+	overrides  []override
+}
+
+type override struct {
+	flag  flag
+	value string
+}
+
+func parseArgs(arguments []string) (args, error) {
+	result := args{}
+	for i := 0; i < len(arguments); i++ {
+		argument := arguments[i]
+		if argument == "--" {
+			result.testFiles = append(result.testFiles, arguments[i+1:]...)
+			break
+		}
+		if !strings.HasPrefix(argument, "--") {
+			result.testFiles = append(result.testFiles, argument)
+			continue
+		}
+		name, value, hasValue := cut(argument, "=")
+		if name == "--config" {
+			if !hasValue {
+				i++
+				if i >= len(arguments) {
+					return args{}, configErrorf("--config: missing argument <file>")
+				}
+				value = arguments[i]
+			}
+			result.configFile = value
+			continue
+		}
+		flag, ok := lookupFlag(name)
+		if !ok {
+			return args{}, configErrorf("unknown flag: %s", name)
+		}
+		if flag.argument == "" {
+			if hasValue {
+				return args{}, configErrorf("%s: doesn't take an argument", name)
+			}
+		} else if !hasValue && !flag.optionalArgument {
+			i++
+			if i >= len(arguments) {
+				return args{}, configErrorf("%s: missing argument <%s>", name, flag.argument)
+			}
+			value = arguments[i]
+		}
+		result.overrides = append(result.overrides, override{flag: flag, value: value})
+	}
+	return result, nil
+}
+
+// rebase makes all paths in the arguments relative to the given project
+// root.
+func (args *args) rebase(root string) error {
+	var err error
+	if args.configFile != "" {
+		args.configFile, err = rebasePath(root, args.configFile)
+		if err != nil {
+			return err
+		}
+	}
+	for i, testFile := range args.testFiles {
+		args.testFiles[i], err = rebasePath(root, testFile)
+		if err != nil {
+			return err
+		}
+	}
+	for i, override := range args.overrides {
+		if override.flag.path {
+			args.overrides[i].value, err = rebasePath(root, override.value)
+			if err != nil {
+				return err
+			}
+		}
+	}
+	return nil
+}
+
+func lookupFlag(name string) (flag, bool) {
+	for _, candidate := range flags {
+		if candidate.name == name {
+			return candidate, true
+		}
+	}
+	return flag{}, false
+}
+
+func splitKeyValue(flagName string, value string) (string, string, error) {
+	key, val, ok := cut(value, "=")
+	if !ok || key == "" {
+		return "", "", configErrorf("%s: expected KEY=VALUE, got: %s", flagName, value)
+	}
+	return key, val, nil
+}
+
+func cut(s string, separator string) (string, string, bool) {
+	if i := strings.Index(s, separator); i >= 0 {
+		return s[:i], s[i+len(separator):], true
+	}
+	return s, "", false
+}
+
+// loadSettings parses the command line arguments, loads the config file
+// and applies the command line flags on top of it.
+func loadSettings(arguments []string) (*Config, []string, error) {
+	args, err := parseArgs(arguments)
+	if err != nil {
+		return nil, nil, err
+	}
+	workingDirectory, err := os.Getwd()
+	if err != nil {
+		return nil, nil, err
+	}
+	root, err := findProjectRoot()
+	if err != nil {
+		return nil, nil, err
+	}
+	err = args.rebase(root)
+	if err != nil {
+		return nil, nil, err
+	}
+	err = os.Chdir(root)
+	if err != nil {
+		return nil, nil, err
+	}
+	workspace, err := findWorkspace()
+	if err != nil {
+		return nil, nil, err
+	}
+	config, err := loadConfig(args.configFile, workspace)
+	if err != nil {
+		return nil, nil, err
+	}
+	for _, override := range args.overrides {
+		err = override.flag.apply(&config, override.value)
+		if err != nil {
+			return nil, nil, err
+		}
+	}
+	config.WorkingDirectory = workingDirectory
+	config.Workspace = workspace
+	if config.Esbuild.Format == "" && isModulePackage() {
+		config.Esbuild.Format = "esm"
+	}
+	err = config.validate()
+	if err != nil {
+		return nil, nil, err
+	}
+	testFiles := args.testFiles
+	if len(testFiles) == 0 && len(config.TestFiles) > 0 {
+		testFiles, err = expandGlobs(".", config.TestFiles, config.Ignore)
+		if err != nil {
+			return nil, nil, err
+		}
+	}
+	return &config, testFiles, nil
+}
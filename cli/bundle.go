@@ -1,33 +1,140 @@
 package cli
 
 import (
+	"path/filepath"
+	"strings"
+
 	"github.com/evanw/esbuild/pkg/api"
 )
 
-func bundle(inputCode string, outputFile string) error {
+// bundle writes the bundled code (and its source map) to outputFile and
+// returns esbuild's metafile.
+func bundle(config *Config, inputCode string, outputFile string) (string, error) {
+	tsconfig, err := loadTsconfig(config)
+	if err != nil {
+		return "", err
+	}
+	targetName := config.Esbuild.Target
+	if _, ok := targets[strings.ToLower(tsconfig.target)]; targetName == "" && ok {
+		targetName = tsconfig.target
+	}
+	target, err := esbuildTarget(targetName)
+	if err != nil {
+		return "", err
+	}
+	jsxFactoryName, jsxFragmentName, inject, err := jsxSettings(config, tsconfig, outputFile)
+	if err != nil {
+		return "", err
+	}
 	buildResult := api.Build(api.BuildOptions{
 		EntryPoints: []string{},
 		Outfile:     outputFile,
 		Bundle:      true,
 		Write:       true,
+		Metafile:    true,
+		Sourcemap:   api.SourceMapLinked,
 		Platform:    api.PlatformNode,
+		Format:      format(config),
+		Banner:      banner(config),
+		Target:      target,
+		JSXFactory:  jsxFactoryName,
+		JSXFragment: jsxFragmentName,
+		Inject:      inject,
+		Tsconfig:    config.Esbuild.Tsconfig,
+		Define:      defines(config),
+		Loader:      esbuildLoaders(config),
 		Stdin: &api.StdinOptions{
 			Contents:   inputCode,
 			ResolveDir: ".",
 			Sourcefile: "<str test runner>",
 		},
-		Plugins: []api.Plugin{injectDirname, nonRelativeImportsAreExternal},
+		Plugins: []api.Plugin{
+			fileLoader(config),
+			mockModules(config, esbuildLoaders(config)),
+			injectDirname(esbuildLoaders(config)),
+			nonRelativeImportsAreExternal(tsconfig, config.Workspace),
+		},
 	})
 	if len(buildResult.Errors) > 0 {
-		formattedErrors := api.FormatMessages(buildResult.Errors, api.FormatMessagesOptions{
+		formattedErrors := api.FormatMessages(addLoaderHints(buildResult.Errors), api.FormatMessagesOptions{
 			Color: true,
 		})
 		for _, error := range formattedErrors {
 			print(error)
 		}
-		return &BundleError{}
+		return "", &BundleError{}
 	}
-	return nil
+	return buildResult.Metafile, nil
+}
+
+var targets = map[string]api.Target{
+	"":       api.DefaultTarget,
+	"esnext": api.ESNext,
+	"es6":    api.ES2015,
+	"es2015": api.ES2015,
+	"es2016": api.ES2016,
+	"es2017": api.ES2017,
+	"es2018": api.ES2018,
+	"es2019": api.ES2019,
+	"es2020": api.ES2020,
+	"es2021": api.ES2021,
+}
+
+func esbuildTarget(name string) (api.Target, error) {
+	target, ok := targets[strings.ToLower(name)]
+	if !ok {
+		return api.DefaultTarget, configErrorf("unsupported esbuild target: %s", name)
+	}
+	return target, nil
+}
+
+func format(config *Config) api.Format {
+	if config.esm() {
+		return api.FormatESModule
+	}
+	return api.FormatCommonJS
+}
+
+// banner provides `require` in ES modules, e.g. for module mocks and
+// `require` calls in test files.
+func banner(config *Config) map[string]string {
+	if !config.esm() {
+		return nil
+	}
+	return map[string]string{
+		"js": "import { createRequire as __strCreateRequire } from \"module\";\n" +
+			"const require = __strCreateRequire(import.meta.url);",
+	}
+}
+
+const jsxFactory = "__strJsx"
+const jsxFragment = "__strJsxFragment"
+
+// jsxSettings returns the JSX factory and fragment, and the files to
+// inject. For the automatic runtime, the factory and fragment come from a
+// module that is written next to the bundle and injected into every file.
+func jsxSettings(config *Config, tsconfig *tsconfig, outputFile string) (string, string, []string, error) {
+	runtime, ok := tsconfig.jsxRuntime()
+	if !ok {
+		return config.Esbuild.JSXFactory, config.Esbuild.JSXFragment, nil, nil
+	}
+	runtimeFile := filepath.Join(filepath.Dir(outputFile), "jsx-runtime.js")
+	err := writeFile(runtimeFile, runtime)
+	if err != nil {
+		return "", "", nil, err
+	}
+	return jsxFactory, jsxFragment, []string{runtimeFile}, nil
+}
+
+func defines(config *Config) map[string]string {
+	result := map[string]string{}
+	for key, value := range fileInfoDefines {
+		result[key] = value
+	}
+	for key, value := range config.Esbuild.Define {
+		result[key] = value
+	}
+	return result
 }
 
 type BundleError struct{}
@@ -36,12 +143,20 @@ func (e *BundleError) Error() string {
 	return "bundle error"
 }
 
-var nonRelativeImportsAreExternal api.Plugin = api.Plugin{
-	Name: "make non relative imports (e.g. from node_modules) external",
-	Setup: func(build api.PluginBuild) {
-		build.OnResolve(api.OnResolveOptions{Filter: "^[^\\.]"},
-			func(args api.OnResolveArgs) (api.OnResolveResult, error) {
-				return api.OnResolveResult{External: true}, nil
-			})
-	},
+// nonRelativeImportsAreExternal makes non relative imports external,
+// unless they are resolved through `paths` or `baseUrl` in the tsconfig or
+// refer to workspace packages, which are bundled from source.
+func nonRelativeImportsAreExternal(tsconfig *tsconfig, workspace *workspace) api.Plugin {
+	return api.Plugin{
+		Name: "make non relative imports (e.g. from node_modules) external",
+		Setup: func(build api.PluginBuild) {
+			build.OnResolve(api.OnResolveOptions{Filter: "^[^\\./]"},
+				func(args api.OnResolveArgs) (api.OnResolveResult, error) {
+					if tsconfig.resolves(args.Path) || workspace.contains(args.Path) {
+						return api.OnResolveResult{}, nil
+					}
+					return api.OnResolveResult{External: true}, nil
+				})
+		},
+	}
 }
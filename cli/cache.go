@@ -0,0 +1,338 @@
+package cli
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"time"
+)
+
+// cacheManifest records the hashes of all files that went into a cached
+// bundle.
+type cacheManifest struct {
+	Inputs map[string]string `json:"inputs"`
+}
+
+func (config *Config) cacheDir() (string, bool) {
+	if config.NoCache {
+		return "", false
+	}
+	if config.CacheDir != "" {
+		return config.CacheDir, true
+	}
+	if info, err := os.Stat("node_modules"); err == nil && info.IsDir() {
+		return filepath.Join("node_modules", ".cache", "str"), true
+	}
+	return "", false
+}
+
+// maxCacheEntries is the number of cached bundles that are kept. Older ones
+// are removed after every run that writes a new bundle.
+const maxCacheEntries = 20
+
+// cachedBundle returns a bundle of the given runner code from the cache
+// directory, if none of its input files changed. Otherwise it bundles the
+// code and stores the result in the cache.
+//
+// Entries are never changed once they're written, since other runs might
+// be executing them. Bundles for the same key get their own entry
+// directories (`<key>-<random>`), and outdated ones are only removed when
+// they're among the least recently used entries.
+func cachedBundle(cacheDir string, config *Config, code string) (string, error) {
+	cacheDir, err := filepath.Abs(cacheDir)
+	if err != nil {
+		return "", err
+	}
+	key, err := cacheKey(config, code)
+	if err != nil {
+		return "", err
+	}
+	if entryDir, ok := findCacheEntry(cacheDir, key, config.bundleFileName()); ok {
+		now := time.Now()
+		// Marks the entry as recently used for pruning.
+		err = os.Chtimes(entryDir, now, now)
+		if err != nil {
+			return "", err
+		}
+		return filepath.Join(entryDir, config.bundleFileName()), nil
+	}
+	err = os.MkdirAll(cacheDir, 0755)
+	if err != nil {
+		return "", err
+	}
+	// The bundle is written into its final directory, which keeps the
+	// relative paths in the source map valid. Entries only count as
+	// complete once they have a manifest, which is written last.
+	entryDir, err := os.MkdirTemp(cacheDir, key+"-")
+	if err != nil {
+		return "", err
+	}
+	bundleFile, err := writeCacheEntry(entryDir, config, code)
+	if err != nil {
+		os.RemoveAll(entryDir)
+		return "", err
+	}
+	err = pruneCache(cacheDir, entryDir)
+	if err != nil {
+		return "", err
+	}
+	return bundleFile, nil
+}
+
+func writeCacheEntry(entryDir string, config *Config, code string) (string, error) {
+	bundleFile := filepath.Join(entryDir, config.bundleFileName())
+	metafile, err := bundle(config, code, bundleFile)
+	if err != nil {
+		return "", err
+	}
+	manifest, err := manifestFromMetafile(metafile)
+	if err != nil {
+		return "", err
+	}
+	encoded, err := json.Marshal(manifest)
+	if err != nil {
+		return "", err
+	}
+	err = writeFile(filepath.Join(entryDir, "manifest.json"), string(encoded))
+	if err != nil {
+		return "", err
+	}
+	return bundleFile, nil
+}
+
+// findCacheEntry returns the most recently used entry for the given key
+// that is up to date.
+func findCacheEntry(cacheDir string, key string, bundleFileName string) (string, bool) {
+	entries, err := cacheEntries(cacheDir)
+	if err != nil {
+		return "", false
+	}
+	for _, entry := range entries {
+		if strings.HasPrefix(entry.Name(), key+"-") &&
+			isUpToDate(filepath.Join(cacheDir, entry.Name()), bundleFileName) {
+			return filepath.Join(cacheDir, entry.Name()), true
+		}
+	}
+	return "", false
+}
+
+// cacheEntries returns the directories in the cache, most recently used
+// first.
+func cacheEntries(cacheDir string) ([]os.FileInfo, error) {
+	dirEntries, err := os.ReadDir(cacheDir)
+	if err != nil {
+		return nil, err
+	}
+	entries := []os.FileInfo{}
+	for _, dirEntry := range dirEntries {
+		info, err := dirEntry.Info()
+		if err == nil && info.IsDir() {
+			entries = append(entries, info)
+		}
+	}
+	sort.SliceStable(entries, func(i, j int) bool {
+		return entries[i].ModTime().After(entries[j].ModTime())
+	})
+	return entries, nil
+}
+
+// pruneCache removes all but the `maxCacheEntries` most recently used
+// entries, but never the given entry, which is about to be used.
+func pruneCache(cacheDir string, entryDir string) error {
+	entries, err := cacheEntries(cacheDir)
+	if err != nil {
+		return err
+	}
+	kept := 1
+	for _, entry := range entries {
+		dir := filepath.Join(cacheDir, entry.Name())
+		if dir == entryDir {
+			continue
+		}
+		if kept < maxCacheEntries {
+			kept++
+			continue
+		}
+		err = os.RemoveAll(dir)
+		if err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// strVersion identifies the running `str` executable. There are no
+// version numbers, so this uses the executable's path, size and
+// modification time.
+func strVersion() (string, error) {
+	executable, err := os.Executable()
+	if err != nil {
+		return "", err
+	}
+	info, err := os.Stat(executable)
+	if err != nil {
+		return "", err
+	}
+	return fmt.Sprintf("%s:%d:%d", executable, info.Size(), info.ModTime().UnixNano()), nil
+}
+
+// cacheKey hashes everything that influences the bundle, except for the
+// input files, which are checked against the manifest.
+func cacheKey(config *Config, code string) (string, error) {
+	version, err := strVersion()
+	if err != nil {
+		return "", err
+	}
+	workingDirectory, err := os.Getwd()
+	if err != nil {
+		return "", err
+	}
+	// esbuild reads these implicitly, so they don't show up in the metafile.
+	implicitInputs := map[string]string{}
+	files := []string{"tsconfig.json", "jsconfig.json", "package.json"}
+	// An invalid tsconfig fails the bundle anyway, so errors are ignored.
+	if tsconfig, err := loadTsconfig(config); err == nil {
+		files = append(files, tsconfig.files...)
+	}
+	if config.Workspace.root != "" {
+		files = append(files, filepath.Join(config.Workspace.root, "package.json"))
+		for _, packageDir := range config.Workspace.packages {
+			files = append(files, filepath.Join(packageDir, "package.json"))
+		}
+	}
+	for _, file := range files {
+		if hash, err := hashFile(file); err == nil {
+			implicitInputs[file] = hash
+		}
+	}
+	key, err := json.Marshal(map[string]interface{}{
+		"version":          version,
+		"workingDirectory": workingDirectory,
+		"code":             code,
+		"esbuild":          config.Esbuild,
+		"moduleMocks":      config.ModuleMocks,
+		"implicitInputs":   implicitInputs,
+	})
+	if err != nil {
+		return "", err
+	}
+	hash := sha256.Sum256(key)
+	return hex.EncodeToString(hash[:16]), nil
+}
+
+func manifestFromMetafile(metafile string) (cacheManifest, error) {
+	var parsed struct {
+		Inputs map[string]json.RawMessage `json:"inputs"`
+	}
+	err := json.Unmarshal([]byte(metafile), &parsed)
+	if err != nil {
+		return cacheManifest{}, err
+	}
+	manifest := cacheManifest{Inputs: map[string]string{}}
+	for input := range parsed.Inputs {
+		// The runner code itself is part of the cache key and the virtual
+		// modules only depend on the files they're generated from.
+		if strings.HasPrefix(input, "<") {
+			continue
+		}
+		file, ok := realFile(input)
+		if !ok {
+			continue
+		}
+		hash, err := hashFile(file)
+		if err != nil {
+			return cacheManifest{}, err
+		}
+		manifest.Inputs[file] = hash
+	}
+	err = addNearbyConfigFiles(manifest)
+	if err != nil {
+		return cacheManifest{}, err
+	}
+	return manifest, nil
+}
+
+// nearbyConfigFiles are the files that esbuild looks for in the directory
+// of every input file and its parents.
+var nearbyConfigFiles = []string{"tsconfig.json", "jsconfig.json", "package.json"}
+
+// addNearbyConfigFiles adds the config files in the directories of the
+// input files and their parents up to the project root. Files that don't
+// exist are recorded with an empty hash, so creating them invalidates the
+// entry as well.
+func addNearbyConfigFiles(manifest cacheManifest) error {
+	root, err := os.Getwd()
+	if err != nil {
+		return err
+	}
+	dirs := map[string]bool{}
+	for file := range manifest.Inputs {
+		absolute, err := filepath.Abs(file)
+		if err != nil {
+			return err
+		}
+		if isInNodeModules(absolute) {
+			continue
+		}
+		dir := filepath.Dir(absolute)
+		for !dirs[dir] && isInsideDir(dir, root) {
+			dirs[dir] = true
+			dir = filepath.Dir(dir)
+		}
+	}
+	for dir := range dirs {
+		for _, name := range nearbyConfigFiles {
+			// Input files are relative to the project root, too.
+			file, err := filepath.Rel(root, filepath.Join(dir, name))
+			if err != nil {
+				return err
+			}
+			if _, ok := manifest.Inputs[file]; !ok {
+				manifest.Inputs[file] = fileHash(file)
+			}
+		}
+	}
+	return nil
+}
+
+func isUpToDate(entryDir string, bundleFileName string) bool {
+	content, err := os.ReadFile(filepath.Join(entryDir, "manifest.json"))
+	if err != nil {
+		return false
+	}
+	manifest := cacheManifest{}
+	if json.Unmarshal(content, &manifest) != nil {
+		return false
+	}
+	for file, expected := range manifest.Inputs {
+		if fileHash(file) != expected {
+			return false
+		}
+	}
+	_, err = os.Stat(filepath.Join(entryDir, bundleFileName))
+	return err == nil
+}
+
+func hashFile(file string) (string, error) {
+	content, err := os.ReadFile(file)
+	if err != nil {
+		return "", err
+	}
+	hash := sha256.Sum256(content)
+	return hex.EncodeToString(hash[:]), nil
+}
+
+// fileHash returns the hash of the given file, or an empty string if it
+// can't be read.
+func fileHash(file string) string {
+	hash, err := hashFile(file)
+	if err != nil {
+		return ""
+	}
+	return hash
+}
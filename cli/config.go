@@ -0,0 +1,238 @@
+package cli
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"os"
+	"os/exec"
+	"path/filepath"
+	"strings"
+)
+
+type Config struct {
+	TestFiles         []string          `json:"testFiles"`
+	Ignore            []string          `json:"ignore"`
+	Timeout           int               `json:"timeout"`
+	Env               map[string]string `json:"env"`
+	EnvFiles          []string          `json:"envFiles"`
+	Esbuild           EsbuildConfig     `json:"esbuild"`
+	SetupFiles        []string          `json:"setupFiles"`
+	GlobalSetup       string            `json:"globalSetup"`
+	GlobalTeardown    string            `json:"globalTeardown"`
+	Capture           bool              `json:"capture"`
+	Slow              int               `json:"slow"`
+	Coverage          bool              `json:"coverage"`
+	CoverageThreshold float64           `json:"coverageThreshold"`
+	CacheDir          string            `json:"cacheDir"`
+	Typecheck         bool              `json:"typecheck"`
+	ModuleMocks       map[string]string `json:"moduleMocks"`
+	Reporters         map[string]string `json:"reporters"`
+	Parallelism       int               `json:"parallelism"`
+	NoCache           bool              `json:"-"`
+	Filter            string            `json:"-"`
+	Seed              *uint32           `json:"-"`
+	Inspect           string            `json:"-"`
+	KeepBundle        string            `json:"-"`
+	PrintRunner       bool              `json:"-"`
+	WorkingDirectory  string            `json:"-"`
+	Workspace         *workspace        `json:"-"`
+}
+
+type EsbuildConfig struct {
+	Target      string            `json:"target"`
+	JSXFactory  string            `json:"jsxFactory"`
+	JSXFragment string            `json:"jsxFragment"`
+	Tsconfig    string            `json:"tsconfig"`
+	Define      map[string]string `json:"define"`
+	Loader      map[string]string `json:"loader"`
+	Format      string            `json:"format"`
+}
+
+type ConfigError struct {
+	message string
+}
+
+func (e *ConfigError) Error() string {
+	return e.message
+}
+
+func configErrorf(format string, a ...interface{}) error {
+	return &ConfigError{message: fmt.Sprintf(format, a...)}
+}
+
+var configFiles = []string{"str.config.ts", "str.config.json"}
+
+func findConfigFile() (string, bool) {
+	for _, file := range configFiles {
+		if _, err := os.Stat(file); err == nil {
+			return file, true
+		}
+	}
+	if packageJson, err := os.ReadFile("package.json"); err == nil {
+		var fields map[string]json.RawMessage
+		if json.Unmarshal(packageJson, &fields) == nil {
+			if _, ok := fields["str"]; ok {
+				return "package.json", true
+			}
+		}
+	}
+	return "", false
+}
+
+func loadConfig(file string, workspace *workspace) (Config, error) {
+	if file == "" {
+		var ok bool
+		file, ok = findConfigFile()
+		if !ok {
+			return Config{}, nil
+		}
+	}
+	var content []byte
+	var err error
+	switch {
+	case filepath.Base(file) == "package.json":
+		content, err = readPackageJsonConfig(file)
+	case strings.HasSuffix(file, ".json"):
+		content, err = os.ReadFile(file)
+	default:
+		content, err = evaluateConfig(file, workspace)
+	}
+	if err != nil {
+		return Config{}, err
+	}
+	return decodeConfig(file, content)
+}
+
+func readPackageJsonConfig(file string) ([]byte, error) {
+	packageJson, err := os.ReadFile(file)
+	if err != nil {
+		return nil, err
+	}
+	var fields map[string]json.RawMessage
+	err = json.Unmarshal(packageJson, &fields)
+	if err != nil {
+		return nil, configErrorf("%s: %s", file, err)
+	}
+	content, ok := fields["str"]
+	if !ok {
+		return nil, configErrorf("%s: no \"str\" field found", file)
+	}
+	return content, nil
+}
+
+func decodeConfig(file string, content []byte) (Config, error) {
+	decoder := json.NewDecoder(bytes.NewReader(content))
+	decoder.DisallowUnknownFields()
+	config := Config{}
+	err := decoder.Decode(&config)
+	if err != nil {
+		return Config{}, configErrorf("%s: %s", file, strings.TrimPrefix(err.Error(), "json: "))
+	}
+	return config, nil
+}
+
+// evaluateConfig bundles and runs a typescript or javascript config file
+// and returns its default export as json.
+func evaluateConfig(file string, workspace *workspace) ([]byte, error) {
+	dir, err := os.MkdirTemp("", "str-config")
+	if err != nil {
+		return nil, err
+	}
+	defer os.RemoveAll(dir)
+	code := fmt.Sprintf(`
+		import config from %s;
+		process.stdout.write(JSON.stringify(config));
+	`, importPath(file))
+	bundleFile := filepath.Join(dir, "config.js")
+	_, err = bundle(&Config{Workspace: workspace}, code, bundleFile)
+	if err != nil {
+		return nil, err
+	}
+	command := exec.Command("node", bundleFile)
+	// The bundle lives in a temporary directory, so node can only find
+	// imports from node_modules through NODE_PATH.
+	nodePath, err := workspace.nodePath()
+	if err != nil {
+		return nil, err
+	}
+	command.Env = append(os.Environ(), nodePath)
+	command.Stderr = os.Stderr
+	output, err := command.Output()
+	if err != nil {
+		return nil, configErrorf("%s: evaluating config file failed: %s", file, err)
+	}
+	return output, nil
+}
+
+func (config *Config) validate() error {
+	if config.Timeout < 0 {
+		return configErrorf("timeout must not be negative: %d", config.Timeout)
+	}
+	if config.Slow < 0 {
+		return configErrorf("slow must not be negative: %d", config.Slow)
+	}
+	if config.Parallelism < 0 {
+		return configErrorf("parallelism must not be negative: %d", config.Parallelism)
+	}
+	if config.CoverageThreshold < 0 || config.CoverageThreshold > 100 {
+		return configErrorf("coverageThreshold must be between 0 and 100: %g", config.CoverageThreshold)
+	}
+	if _, err := esbuildTarget(config.Esbuild.Target); err != nil {
+		return err
+	}
+	if err := validateLoaders(config.Esbuild.Loader); err != nil {
+		return err
+	}
+	switch config.Esbuild.Format {
+	case "", "cjs", "esm":
+	default:
+		return configErrorf("unsupported format: %s (supported formats: cjs, esm)", config.Esbuild.Format)
+	}
+	for reporter := range config.Reporters {
+		switch reporter {
+		case "json", "junit":
+		default:
+			return configErrorf("unsupported reporter: %s (supported reporters: json, junit)", reporter)
+		}
+	}
+	return nil
+}
+
+// isModulePackage checks whether the package.json in the current directory
+// has `"type": "module"`.
+func isModulePackage() bool {
+	packageJson, err := os.ReadFile("package.json")
+	if err != nil {
+		return false
+	}
+	var fields struct {
+		Type string `json:"type"`
+	}
+	if json.Unmarshal(packageJson, &fields) != nil {
+		return false
+	}
+	return fields.Type == "module"
+}
+
+func (config *Config) esm() bool {
+	return config.Esbuild.Format == "esm"
+}
+
+// bundleFileName is the name of the bundle file. It has to end in `.mjs`
+// for node to load the bundle as an ES module.
+func (config *Config) bundleFileName() string {
+	if config.esm() {
+		return "main.mjs"
+	}
+	return "main.js"
+}
+
+func (config *Config) coverageEnabled() bool {
+	return config.Coverage || config.CoverageThreshold > 0
+}
+
+func jsString(s string) string {
+	encoded, _ := json.Marshal(s)
+	return string(encoded)
+}
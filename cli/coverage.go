@@ -0,0 +1,354 @@
+package cli
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+)
+
+const coverageReportDir = "coverage"
+
+type v8Coverage struct {
+	Result []v8Script `json:"result"`
+}
+
+type v8Script struct {
+	Url       string       `json:"url"`
+	Functions []v8Function `json:"functions"`
+}
+
+type v8Function struct {
+	Ranges []v8Range `json:"ranges"`
+}
+
+type v8Range struct {
+	StartOffset int `json:"startOffset"`
+	EndOffset   int `json:"endOffset"`
+	Count       int `json:"count"`
+}
+
+// lineCoverage maps source files to their executable lines (one-based)
+// and how often they were executed.
+type lineCoverage map[string]map[int]int
+
+func (runner *runner) reportCoverage(v8Dir string, bundleFile string, testFiles []string) error {
+	processes, err := readV8Coverage(v8Dir, bundleFile)
+	if err != nil {
+		return err
+	}
+	coverage, err := mapCoverage(processes, bundleFile, testFiles)
+	if err != nil {
+		return err
+	}
+	err = os.MkdirAll(coverageReportDir, 0755)
+	if err != nil {
+		return err
+	}
+	err = writeFile(filepath.Join(coverageReportDir, "lcov.info"), coverage.lcov())
+	if err != nil {
+		return err
+	}
+	summary, err := json.MarshalIndent(coverage.summary(), "", "  ")
+	if err != nil {
+		return err
+	}
+	err = writeFile(filepath.Join(coverageReportDir, "coverage-summary.json"), string(summary)+"\n")
+	if err != nil {
+		return err
+	}
+	fmt.Fprint(os.Stderr, coverage.table())
+	threshold := runner.config.CoverageThreshold
+	if total := coverage.total(); threshold > 0 && total.Pct < threshold {
+		fmt.Fprintf(os.Stderr, "Line coverage of %.2f%% is below the threshold of %g%%.\n", total.Pct, threshold)
+		runner.failed = true
+	}
+	return nil
+}
+
+// readV8Coverage reads the coverage files that node writes into
+// NODE_V8_COVERAGE and returns the ranges for the given bundle file. Every
+// process (e.g. every parallel worker) writes its own file, so there's one
+// list of ranges per process.
+func readV8Coverage(v8Dir string, bundleFile string) ([][]v8Range, error) {
+	realBundleFile, err := filepath.EvalSymlinks(bundleFile)
+	if err != nil {
+		return nil, err
+	}
+	url := "file://" + filepath.ToSlash(realBundleFile)
+	entries, err := os.ReadDir(v8Dir)
+	if err != nil {
+		return nil, err
+	}
+	processes := [][]v8Range{}
+	for _, entry := range entries {
+		content, err := os.ReadFile(filepath.Join(v8Dir, entry.Name()))
+		if err != nil {
+			return nil, err
+		}
+		coverage := v8Coverage{}
+		err = json.Unmarshal(content, &coverage)
+		if err != nil {
+			return nil, fmt.Errorf("%s: %s", entry.Name(), err)
+		}
+		ranges := []v8Range{}
+		for _, script := range coverage.Result {
+			if script.Url != url {
+				continue
+			}
+			for _, function := range script.Functions {
+				ranges = append(ranges, function.Ranges...)
+			}
+		}
+		processes = append(processes, ranges)
+	}
+	return processes, nil
+}
+
+// mapCoverage maps the V8 ranges through the bundle's source map back to
+// the original source files. Each line gets the highest count of the
+// generated code that maps to it. node_modules (including the `str`
+// library) and the test files themselves are excluded.
+func mapCoverage(processes [][]v8Range, bundleFile string, testFiles []string) (lineCoverage, error) {
+	generated, err := os.ReadFile(bundleFile)
+	if err != nil {
+		return nil, err
+	}
+	sourceMap, err := readSourceMap(bundleFile + ".map")
+	if err != nil {
+		return nil, err
+	}
+	mappings, err := sourceMap.decodeMappings()
+	if err != nil {
+		return nil, err
+	}
+	sources, err := coveredSources(sourceMap.Sources, filepath.Dir(bundleFile), testFiles)
+	if err != nil {
+		return nil, err
+	}
+	// esbuild escapes non-ASCII characters, so byte offsets are the same
+	// as V8's UTF-16 offsets.
+	lineOffsets := []int{0}
+	for i, char := range generated {
+		if char == '\n' {
+			lineOffsets = append(lineOffsets, i+1)
+		}
+	}
+	result := lineCoverage{}
+	for _, mapping := range mappings {
+		source, ok := sources[mapping.source]
+		if !ok || mapping.generatedLine >= len(lineOffsets) {
+			continue
+		}
+		count := 0
+		for _, ranges := range processes {
+			count += countAt(ranges, lineOffsets[mapping.generatedLine]+mapping.generatedColumn)
+		}
+		if result[source] == nil {
+			result[source] = map[int]int{}
+		}
+		line := mapping.originalLine + 1
+		if previous, ok := result[source][line]; !ok || count > previous {
+			result[source][line] = count
+		}
+	}
+	return result, nil
+}
+
+func coveredSources(sources []string, sourceMapDir string, testFiles []string) (map[int]string, error) {
+	workingDirectory, err := os.Getwd()
+	if err != nil {
+		return nil, err
+	}
+	excluded := map[string]bool{}
+	for _, testFile := range testFiles {
+		absolute, err := filepath.Abs(testFile)
+		if err != nil {
+			return nil, err
+		}
+		excluded[absolute] = true
+	}
+	// The JSX runtime module that's written next to the bundle.
+	excluded[filepath.Join(sourceMapDir, "jsx-runtime.js")] = true
+	result := map[int]string{}
+	for i, source := range sources {
+		if strings.HasPrefix(filepath.Base(source), "<") {
+			continue
+		}
+		file, ok := realFile(source)
+		if !ok {
+			continue
+		}
+		absolute := file
+		if !filepath.IsAbs(absolute) {
+			absolute = filepath.Join(sourceMapDir, filepath.FromSlash(file))
+		}
+		if excluded[absolute] || isInNodeModules(absolute) {
+			continue
+		}
+		relative, err := filepath.Rel(workingDirectory, absolute)
+		if err != nil {
+			return nil, err
+		}
+		result[i] = filepath.ToSlash(relative)
+	}
+	return result, nil
+}
+
+func isInNodeModules(file string) bool {
+	for _, segment := range strings.Split(filepath.ToSlash(file), "/") {
+		if segment == "node_modules" {
+			return true
+		}
+	}
+	return false
+}
+
+// countAt returns the count of the innermost range that contains the
+// given offset.
+func countAt(ranges []v8Range, offset int) int {
+	count := 0
+	size := -1
+	for _, r := range ranges {
+		if r.StartOffset <= offset && offset < r.EndOffset {
+			if size < 0 || r.EndOffset-r.StartOffset <= size {
+				count = r.Count
+				size = r.EndOffset - r.StartOffset
+			}
+		}
+	}
+	return count
+}
+
+func (coverage lineCoverage) files() []string {
+	files := []string{}
+	for file := range coverage {
+		files = append(files, file)
+	}
+	sort.Strings(files)
+	return files
+}
+
+func sortedLines(lines map[int]int) []int {
+	result := []int{}
+	for line := range lines {
+		result = append(result, line)
+	}
+	sort.Ints(result)
+	return result
+}
+
+type lineSummary struct {
+	Total   int     `json:"total"`
+	Covered int     `json:"covered"`
+	Pct     float64 `json:"pct"`
+}
+
+func summarize(lines map[int]int) lineSummary {
+	result := lineSummary{Total: len(lines)}
+	for _, count := range lines {
+		if count > 0 {
+			result.Covered++
+		}
+	}
+	return result.withPct()
+}
+
+func (summary lineSummary) withPct() lineSummary {
+	if summary.Total == 0 {
+		summary.Pct = 100
+	} else {
+		summary.Pct = float64(summary.Covered) * 100 / float64(summary.Total)
+	}
+	return summary
+}
+
+func (summary lineSummary) String() string {
+	return fmt.Sprintf("%.2f%% (%d/%d)", summary.Pct, summary.Covered, summary.Total)
+}
+
+func (coverage lineCoverage) total() lineSummary {
+	result := lineSummary{}
+	for _, lines := range coverage {
+		summary := summarize(lines)
+		result.Total += summary.Total
+		result.Covered += summary.Covered
+	}
+	return result.withPct()
+}
+
+func (coverage lineCoverage) summary() map[string]map[string]lineSummary {
+	result := map[string]map[string]lineSummary{
+		"total": {"lines": coverage.total()},
+	}
+	for file, lines := range coverage {
+		result[file] = map[string]lineSummary{"lines": summarize(lines)}
+	}
+	return result
+}
+
+func (coverage lineCoverage) lcov() string {
+	result := ""
+	for _, file := range coverage.files() {
+		lines := coverage[file]
+		result += "TN:\n"
+		result += fmt.Sprintf("SF:%s\n", file)
+		for _, line := range sortedLines(lines) {
+			result += fmt.Sprintf("DA:%d,%d\n", line, lines[line])
+		}
+		summary := summarize(lines)
+		result += fmt.Sprintf("LF:%d\n", summary.Total)
+		result += fmt.Sprintf("LH:%d\n", summary.Covered)
+		result += "end_of_record\n"
+	}
+	return result
+}
+
+func (coverage lineCoverage) table() string {
+	rows := [][]string{{"File", "Lines", "Uncovered lines"}}
+	for _, file := range coverage.files() {
+		lines := coverage[file]
+		rows = append(rows, []string{file, summarize(lines).String(), uncoveredLines(lines)})
+	}
+	rows = append(rows, []string{"All files", coverage.total().String(), ""})
+	widths := make([]int, len(rows[0]))
+	for _, row := range rows {
+		for i, cell := range row {
+			if len(cell) > widths[i] {
+				widths[i] = len(cell)
+			}
+		}
+	}
+	result := "\nCoverage:\n"
+	for _, row := range rows {
+		cells := []string{}
+		for i, cell := range row {
+			cells = append(cells, fmt.Sprintf("%-*s", widths[i], cell))
+		}
+		result += strings.TrimRight(strings.Join(cells, " | "), " ") + "\n"
+	}
+	return result
+}
+
+// uncoveredLines formats the uncovered lines as ranges, e.g. "3, 5-7".
+func uncoveredLines(lines map[int]int) string {
+	ranges := []string{}
+	executable := sortedLines(lines)
+	for i := 0; i < len(executable); i++ {
+		if lines[executable[i]] > 0 {
+			continue
+		}
+		start := executable[i]
+		for i+1 < len(executable) && lines[executable[i+1]] == 0 {
+			i++
+		}
+		if executable[i] == start {
+			ranges = append(ranges, fmt.Sprint(start))
+		} else {
+			ranges = append(ranges, fmt.Sprintf("%d-%d", start, executable[i]))
+		}
+	}
+	return strings.Join(ranges, ", ")
+}
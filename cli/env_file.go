@@ -0,0 +1,57 @@
+package cli
+
+import (
+	"os"
+	"strings"
+)
+
+// readEnvFiles reads files in the `.env` format and returns their
+// variables as `KEY=VALUE` strings. Later files take precedence.
+func readEnvFiles(files []string) ([]string, error) {
+	result := []string{}
+	for _, file := range files {
+		variables, err := readEnvFile(file)
+		if err != nil {
+			return nil, err
+		}
+		result = append(result, variables...)
+	}
+	return result, nil
+}
+
+func readEnvFile(file string) ([]string, error) {
+	content, err := os.ReadFile(file)
+	if err != nil {
+		return nil, configErrorf("%s", err)
+	}
+	result := []string{}
+	for i, line := range strings.Split(string(content), "\n") {
+		line = strings.TrimSpace(line)
+		if line == "" || strings.HasPrefix(line, "#") {
+			continue
+		}
+		line = strings.TrimPrefix(line, "export ")
+		key, value, ok := cut(line, "=")
+		key = strings.TrimSpace(key)
+		if !ok || key == "" {
+			return nil, configErrorf("%s:%d: expected KEY=VALUE, got: %s", file, i+1, line)
+		}
+		result = append(result, key+"="+unquoteEnvValue(strings.TrimSpace(value)))
+	}
+	return result, nil
+}
+
+// unquoteEnvValue removes quotes around values. Double quoted values can
+// contain `\n` escapes.
+func unquoteEnvValue(value string) string {
+	if len(value) >= 2 {
+		first, last := value[0], value[len(value)-1]
+		if first == '\'' && last == '\'' {
+			return value[1 : len(value)-1]
+		}
+		if first == '"' && last == '"' {
+			return strings.ReplaceAll(value[1:len(value)-1], `\n`, "\n")
+		}
+	}
+	return value
+}
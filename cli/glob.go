@@ -0,0 +1,77 @@
+package cli
+
+import (
+	"io/fs"
+	"path"
+	"path/filepath"
+	"strings"
+)
+
+// expandGlobs returns all files below `dir` that match one of `patterns`
+// and none of `ignore`. Patterns are matched against slash-separated paths
+// relative to `dir`. `**` matches any number of directories.
+func expandGlobs(dir string, patterns []string, ignore []string) ([]string, error) {
+	files := []string{}
+	err := filepath.WalkDir(dir, func(file string, entry fs.DirEntry, err error) error {
+		if err != nil {
+			return err
+		}
+		relative, err := filepath.Rel(dir, file)
+		if err != nil {
+			return err
+		}
+		relative = filepath.ToSlash(relative)
+		if entry.IsDir() {
+			if relative != "." && (entry.Name() == "node_modules" || entry.Name() == ".git") {
+				return filepath.SkipDir
+			}
+			return nil
+		}
+		if matchesAny(patterns, relative) && !matchesAny(ignore, relative) {
+			files = append(files, relative)
+		}
+		return nil
+	})
+	if err != nil {
+		return nil, err
+	}
+	return files, nil
+}
+
+func matchesAny(patterns []string, file string) bool {
+	for _, pattern := range patterns {
+		if matchGlob(pattern, file) {
+			return true
+		}
+	}
+	return false
+}
+
+func matchGlob(pattern string, file string) bool {
+	return matchSegments(
+		strings.Split(path.Clean(pattern), "/"),
+		strings.Split(file, "/"),
+	)
+}
+
+func matchSegments(pattern []string, file []string) bool {
+	if len(pattern) == 0 {
+		return len(file) == 0
+	}
+	if pattern[0] == "**" {
+		for i := 0; i <= len(file); i++ {
+			if matchSegments(pattern[1:], file[i:]) {
+				return true
+			}
+		}
+		return false
+	}
+	if len(file) == 0 {
+		return false
+	}
+	matched, err := path.Match(pattern[0], file[0])
+	if err != nil || !matched {
+		return false
+	}
+	return matchSegments(pattern[1:], file[1:])
+}
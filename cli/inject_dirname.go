@@ -3,40 +3,135 @@ package cli
 import (
 	"fmt"
 	"io/ioutil"
-	"log"
+	"net/url"
 	"path/filepath"
+	"strings"
 
 	"github.com/evanw/esbuild/pkg/api"
 )
 
-var injectDirname api.Plugin = api.Plugin{
-	Name: "inject __dirname",
-	Setup: func(build api.PluginBuild) {
-		options := api.OnLoadOptions{Filter: "\\.(js|jsx|ts|tsx)$"}
-		build.OnLoad(options, onLoad)
-	},
+// injectDirname provides `__dirname`, `__filename` and `import.meta.url`
+// for the original source files. They're imported under other names from a
+// virtual module with an import statement that gets appended to the source
+// file. Since imports are hoisted, this works without shifting any source
+// locations. esbuild's `Define` then replaces only the references to the
+// global `__dirname` and `__filename`, so files that declare or import
+// these names themselves keep their own bindings.
+func injectDirname(configuredLoaders map[string]api.Loader) api.Plugin {
+	return api.Plugin{
+		Name: "inject __dirname",
+		Setup: func(build api.PluginBuild) {
+			options := api.OnLoadOptions{Filter: "\\.(js|jsx|ts|tsx|mjs|cjs|mts|cts)$"}
+			build.OnLoad(options, func(args api.OnLoadArgs) (api.OnLoadResult, error) {
+				return onLoad(configuredLoaders, args)
+			})
+			build.OnResolve(api.OnResolveOptions{Filter: "^" + fileInfoPrefix},
+				func(args api.OnResolveArgs) (api.OnResolveResult, error) {
+					return api.OnResolveResult{
+						Path:      strings.TrimPrefix(args.Path, fileInfoPrefix),
+						Namespace: fileInfoNamespace,
+					}, nil
+				})
+			build.OnLoad(api.OnLoadOptions{Filter: ".*", Namespace: fileInfoNamespace}, onLoadFileInfo)
+		},
+	}
+}
+
+const fileInfoPrefix = "str-file-info:"
+const fileInfoNamespace = "str-file-info"
+
+// These replace `__dirname`, `__filename` and `import.meta.url` through
+// esbuild's `Define`.
+const dirnameVariable = "__strDirname"
+const filenameVariable = "__strFilename"
+const importMetaUrl = "__strImportMetaUrl"
+
+var fileInfoDefines = map[string]string{
+	"__dirname":       dirnameVariable,
+	"__filename":      filenameVariable,
+	"import.meta.url": importMetaUrl,
 }
 
-func onLoad(args api.OnLoadArgs) (api.OnLoadResult, error) {
-	__dirname := filepath.Dir(args.Path)
+func onLoad(configuredLoaders map[string]api.Loader, args api.OnLoadArgs) (api.OnLoadResult, error) {
+	loader, err := pickLoader(configuredLoaders, args.Path)
+	if err != nil {
+		return api.OnLoadResult{}, err
+	}
+	if !isSourceLoader(loader) {
+		// Leave e.g. `.js` files that are configured as `text` to esbuild.
+		return api.OnLoadResult{}, nil
+	}
 	code, err := ioutil.ReadFile(args.Path)
 	if err != nil {
 		return api.OnLoadResult{}, err
 	}
-	contents := fmt.Sprintf("const __dirname = '%s';", __dirname) + string(code)
+	contents := injectFileInfo(args.Path, string(code))
 	result := api.OnLoadResult{
 		Contents: &contents,
-		Loader:   pickLoader(args),
+		Loader:   loader,
 	}
 	return result, nil
 }
 
-func pickLoader(args api.OnLoadArgs) api.Loader {
-	loader, ok := loaders[filepath.Ext(args.Path)]
+// injectFileInfo appends the import of the file info variables that the
+// given code uses.
+func injectFileInfo(file string, code string) string {
+	names := fileInfoVariables(code)
+	if len(names) == 0 {
+		return code
+	}
+	return code + fmt.Sprintf("\nimport { %s } from %s;\n",
+		strings.Join(names, ", "), jsString(fileInfoPrefix+file))
+}
+
+// fileInfoVariables returns the variables from the file info module that
+// the given code might use. Importing a variable that ends up unused, e.g.
+// because the code declares its own `__dirname`, is harmless.
+func fileInfoVariables(code string) []string {
+	names := []string{}
+	for _, original := range []string{"__dirname", "__filename", "import.meta.url"} {
+		if strings.Contains(code, original) {
+			names = append(names, fileInfoDefines[original])
+		}
+	}
+	return names
+}
+
+func onLoadFileInfo(args api.OnLoadArgs) (api.OnLoadResult, error) {
+	fileUrl := url.URL{Scheme: "file", Path: filepath.ToSlash(args.Path)}
+	contents := fmt.Sprintf(
+		"export const %s = %s;\nexport const %s = %s;\nexport const %s = %s;\n",
+		dirnameVariable,
+		jsString(filepath.Dir(args.Path)),
+		filenameVariable,
+		jsString(args.Path),
+		importMetaUrl,
+		jsString(fileUrl.String()),
+	)
+	return api.OnLoadResult{
+		Contents: &contents,
+		Loader:   api.LoaderJS,
+	}, nil
+}
+
+func pickLoader(configuredLoaders map[string]api.Loader, file string) (api.Loader, error) {
+	extension := filepath.Ext(file)
+	if loader, ok := configuredLoaders[extension]; ok {
+		return loader, nil
+	}
+	loader, ok := loaders[extension]
 	if !ok {
-		log.Fatalf("impossible extension: %s", filepath.Ext(args.Path))
+		return api.LoaderNone, fmt.Errorf("no loader configured for %q files", extension)
+	}
+	return loader, nil
+}
+
+func isSourceLoader(loader api.Loader) bool {
+	switch loader {
+	case api.LoaderJS, api.LoaderJSX, api.LoaderTS, api.LoaderTSX:
+		return true
 	}
-	return loader
+	return false
 }
 
 var loaders = map[string]api.Loader{
@@ -44,4 +139,8 @@ var loaders = map[string]api.Loader{
 	".jsx": api.LoaderJSX,
 	".ts":  api.LoaderTS,
 	".tsx": api.LoaderTSX,
+	".mjs": api.LoaderJS,
+	".cjs": api.LoaderJS,
+	".mts": api.LoaderTS,
+	".cts": api.LoaderTS,
 }
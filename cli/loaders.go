@@ -0,0 +1,103 @@
+package cli
+
+import (
+	"fmt"
+	"regexp"
+	"sort"
+	"strings"
+
+	"github.com/evanw/esbuild/pkg/api"
+)
+
+// loaderNames are the loaders that can be configured for file extensions
+// with `esbuild.loader` or `--loader`.
+var loaderNames = map[string]api.Loader{
+	"js":      api.LoaderJS,
+	"jsx":     api.LoaderJSX,
+	"ts":      api.LoaderTS,
+	"tsx":     api.LoaderTSX,
+	"json":    api.LoaderJSON,
+	"text":    api.LoaderText,
+	"base64":  api.LoaderBase64,
+	"binary":  api.LoaderBinary,
+	"dataurl": api.LoaderDataURL,
+	"file":    api.LoaderFile,
+}
+
+func validateLoaders(loaders map[string]string) error {
+	for extension, name := range loaders {
+		if !strings.HasPrefix(extension, ".") {
+			return configErrorf("loader: extensions must start with a dot: %s", extension)
+		}
+		if _, ok := loaderNames[name]; !ok {
+			names := []string{}
+			for name := range loaderNames {
+				names = append(names, name)
+			}
+			sort.Strings(names)
+			return configErrorf("unsupported loader for %s: %s (supported loaders: %s)",
+				extension, name, strings.Join(names, ", "))
+		}
+	}
+	return nil
+}
+
+// esbuildLoaders returns the configured loaders. Extensions with the `file`
+// loader are left out, since those files are handled by `fileLoader`.
+func esbuildLoaders(config *Config) map[string]api.Loader {
+	result := map[string]api.Loader{}
+	for extension, name := range config.Esbuild.Loader {
+		if loader := loaderNames[name]; loader != api.LoaderFile {
+			result[extension] = loader
+		}
+	}
+	return result
+}
+
+// fileLoader makes imports of files with the `file` loader evaluate to the
+// absolute path of the imported file. (esbuild's own `file` loader would
+// copy the files next to the bundle, which lives in a temporary or cache
+// directory.)
+func fileLoader(config *Config) api.Plugin {
+	extensions := []string{}
+	for extension, name := range config.Esbuild.Loader {
+		if name == "file" {
+			extensions = append(extensions, regexp.QuoteMeta(extension))
+		}
+	}
+	return api.Plugin{
+		Name: "file loader",
+		Setup: func(build api.PluginBuild) {
+			if len(extensions) == 0 {
+				return
+			}
+			filter := "(" + strings.Join(extensions, "|") + ")$"
+			build.OnLoad(api.OnLoadOptions{Filter: filter, Namespace: "file"},
+				func(args api.OnLoadArgs) (api.OnLoadResult, error) {
+					contents := fmt.Sprintf("module.exports = %s;\n", jsString(args.Path))
+					return api.OnLoadResult{
+						Contents: &contents,
+						Loader:   api.LoaderJS,
+					}, nil
+				})
+		},
+	}
+}
+
+var noLoaderError = regexp.MustCompile(`^No loader is configured for "([^"]*)" files`)
+
+// addLoaderHints adds a note about configuring loaders to errors about
+// imports of unsupported file types.
+func addLoaderHints(messages []api.Message) []api.Message {
+	for i, message := range messages {
+		if match := noLoaderError.FindStringSubmatch(message.Text); match != nil {
+			extension := match[1]
+			messages[i].Notes = append(message.Notes, api.Note{
+				Text: fmt.Sprintf(
+					"You can configure a loader for %q files with e.g. \"--loader %s=text\".",
+					extension, extension),
+			})
+		}
+	}
+	return messages
+}
@@ -0,0 +1,646 @@
+package cli
+
+import (
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+	"sync"
+
+	"github.com/evanw/esbuild/pkg/api"
+)
+
+// Module mocks replace modules with fakes. They're either declared in the
+// config (`moduleMocks`), which replaces a module with another file
+// everywhere, or with `mockModule("./http", () => fake)` in a test file.
+//
+// Mocks from `mockModule` only apply to the mocking test file and the
+// modules it imports. To achieve that, the test file's relative imports
+// are loaded again in a separate namespace, with the test file in their
+// paths (e.g. `str-mock-scope:/p/client.ts?mockedIn=/p/index.test.ts`).
+// The mocked modules themselves are virtual modules that call the factory
+// from the test file. Since they're imported before the test file runs,
+// the factories can't use imports or variables from the test file, which
+// is reported as a bundle error.
+
+const mockScopeNamespace = "str-mock-scope"
+const mockedModuleNamespace = "str-mocked-module"
+const mockedInSeparator = "?mockedIn="
+const mockSeparator = "?mock="
+
+type moduleMock struct {
+	specifier string
+	factory   string
+	// offset is where the `mockModule` call starts in the test file.
+	offset int
+}
+
+// mockError is a `mockModule` call that can't be used, at the given offset
+// in the test file.
+type mockError struct {
+	offset  int
+	message string
+}
+
+// parseModuleMocks finds calls like `mockModule("./http", () => fake)` and
+// returns the mocked specifiers together with the source of the factories.
+// Calls inside of comments and strings are ignored, calls that can't be
+// parsed are returned as errors.
+func parseModuleMocks(code string) ([]moduleMock, []mockError) {
+	mocks := []moduleMock{}
+	errors := []mockError{}
+	previous := byte(0)
+	lastWord := ""
+	for i := 0; i < len(code); {
+		if end := skipNonCode(code, i, previous); end > i {
+			i = end
+			previous = '"'
+			continue
+		}
+		if !isIdentifierChar(code[i]) {
+			if !strings.ContainsRune(" \t\r\n", rune(code[i])) {
+				previous = code[i]
+			}
+			i++
+			continue
+		}
+		start := i
+		for i < len(code) && isIdentifierChar(code[i]) {
+			i++
+		}
+		word := code[start:i]
+		previous = code[i-1]
+		isDeclaration := lastWord == "function"
+		lastWord = word
+		rest := skipWhitespace(code[i:])
+		if word != "mockModule" || isDeclaration || !strings.HasPrefix(rest, "(") {
+			continue
+		}
+		mock, message := parseMockModuleCall(skipWhitespace(rest[1:]))
+		if message != "" {
+			errors = append(errors, mockError{offset: start, message: message})
+			continue
+		}
+		mock.offset = start
+		mocks = append(mocks, mock)
+	}
+	return mocks, errors
+}
+
+// parseMockModuleCall parses the arguments of a `mockModule` call.
+func parseMockModuleCall(arguments string) (moduleMock, string) {
+	specifier, length, ok := parseStringLiteral(arguments)
+	if !ok {
+		return moduleMock{}, "mockModule: the module has to be given as a string literal"
+	}
+	rest := skipWhitespace(arguments[length:])
+	if !strings.HasPrefix(rest, ",") {
+		return moduleMock{}, "mockModule: expected a factory function as the second argument"
+	}
+	factory, ok := scanArgument(rest[1:])
+	if !ok || strings.TrimSpace(factory) == "" {
+		return moduleMock{}, "mockModule: can't find the end of the factory function"
+	}
+	return moduleMock{
+		specifier: specifier,
+		factory:   strings.TrimSpace(factory),
+	}, ""
+}
+
+func isIdentifierChar(char byte) bool {
+	return char == '_' || char == '$' ||
+		'a' <= char && char <= 'z' || 'A' <= char && char <= 'Z' || '0' <= char && char <= '9' ||
+		char >= 0x80
+}
+
+// skipWhitespace skips whitespace and comments.
+func skipWhitespace(code string) string {
+	for {
+		code = strings.TrimLeft(code, " \t\r\n")
+		if !strings.HasPrefix(code, "//") && !strings.HasPrefix(code, "/*") {
+			return code
+		}
+		code = code[skipNonCode(code, 0, 0):]
+	}
+}
+
+// skipNonCode returns the index after the string, template literal,
+// comment or regular expression that starts at code[i], or i if there's
+// none. `previous` is the last character before that, which tells
+// regular expressions apart from divisions.
+func skipNonCode(code string, i int, previous byte) int {
+	switch {
+	case code[i] == '"' || code[i] == '\'':
+		return skipQuoted(code, i, code[i])
+	case code[i] == '`':
+		return skipTemplate(code, i)
+	case strings.HasPrefix(code[i:], "//"):
+		end := strings.IndexByte(code[i:], '\n')
+		if end < 0 {
+			return len(code)
+		}
+		return i + end
+	case strings.HasPrefix(code[i:], "/*"):
+		end := strings.Index(code[i+2:], "*/")
+		if end < 0 {
+			return len(code)
+		}
+		return i + 2 + end + 2
+	case code[i] == '/' && (previous == 0 || strings.ContainsRune("(,=:[!&|?{};+-*%<>~^", rune(previous))):
+		return skipQuoted(code, i, '/')
+	}
+	return i
+}
+
+// skipQuoted skips a string or regular expression that ends with the given
+// delimiter.
+func skipQuoted(code string, i int, delimiter byte) int {
+	inClass := false
+	for i++; i < len(code); i++ {
+		switch {
+		case code[i] == '\\':
+			i++
+		case code[i] == '\n':
+			return i
+		case delimiter == '/' && code[i] == '[':
+			inClass = true
+		case delimiter == '/' && code[i] == ']':
+			inClass = false
+		case code[i] == delimiter && !inClass:
+			return i + 1
+		}
+	}
+	return len(code)
+}
+
+// skipTemplate skips a template literal, including the code in its
+// `${}` substitutions.
+func skipTemplate(code string, i int) int {
+	for i++; i < len(code); i++ {
+		switch {
+		case code[i] == '\\':
+			i++
+		case code[i] == '`':
+			return i + 1
+		case strings.HasPrefix(code[i:], "${"):
+			i = skipSubstitution(code, i+2)
+		}
+	}
+	return len(code)
+}
+
+// skipSubstitution returns the index of the `}` that closes the code
+// starting at i.
+func skipSubstitution(code string, i int) int {
+	depth := 0
+	previous := byte('{')
+	for i < len(code) {
+		if end := skipNonCode(code, i, previous); end > i {
+			i = end
+			previous = '"'
+			continue
+		}
+		switch code[i] {
+		case '{':
+			depth++
+		case '}':
+			if depth == 0 {
+				return i
+			}
+			depth--
+		}
+		if !strings.ContainsRune(" \t\r\n", rune(code[i])) {
+			previous = code[i]
+		}
+		i++
+	}
+	return len(code)
+}
+
+// parseStringLiteral parses a string literal without escape sequences at
+// the start of the given code and returns its value and length.
+func parseStringLiteral(code string) (string, int, bool) {
+	if code == "" || !strings.ContainsRune(`"'`+"`", rune(code[0])) {
+		return "", 0, false
+	}
+	end := strings.IndexByte(code[1:], code[0])
+	if end < 0 {
+		return "", 0, false
+	}
+	value := code[1 : end+1]
+	if strings.ContainsAny(value, "\\\n") || strings.Contains(value, "${") {
+		return "", 0, false
+	}
+	return value, end + 2, true
+}
+
+// scanArgument returns the code of a function argument, i.e. everything up
+// to the next `,` or `)` outside of brackets, strings and comments.
+func scanArgument(code string) (string, bool) {
+	depth := 0
+	previous := byte('(')
+	for i := 0; i < len(code); {
+		if end := skipNonCode(code, i, previous); end > i {
+			i = end
+			previous = '"'
+			continue
+		}
+		switch char := code[i]; {
+		case char == '(' || char == '[' || char == '{':
+			depth++
+		case char == ')' || char == ']' || char == '}':
+			if depth == 0 {
+				return code[:i], char == ')'
+			}
+			depth--
+		case char == ',' && depth == 0:
+			return code[:i], true
+		}
+		if !strings.ContainsRune(" \t\r\n", rune(code[i])) {
+			previous = code[i]
+		}
+		i++
+	}
+	return "", false
+}
+
+// mockTarget returns what an import refers to, so that imports can be
+// compared with mocked specifiers: the absolute path without extension
+// for relative imports, and the import path itself otherwise.
+func mockTarget(dir string, importPath string) string {
+	if !strings.HasPrefix(importPath, ".") && !filepath.IsAbs(importPath) {
+		return importPath
+	}
+	target := filepath.Join(dir, importPath)
+	if _, ok := loaders[filepath.Ext(target)]; ok {
+		target = strings.TrimSuffix(target, filepath.Ext(target))
+	}
+	return strings.TrimSuffix(target, string(filepath.Separator)+"index")
+}
+
+// realFile returns the file that a path from a metafile or a source map
+// refers to. It returns false for virtual modules.
+func realFile(path string) (string, bool) {
+	namespace, rest, ok := cut(path, ":")
+	if !ok {
+		return path, true
+	}
+	switch namespace {
+	case fileInfoNamespace, mockedModuleNamespace:
+		return "", false
+	case mockScopeNamespace:
+		file, _, _ := cut(rest, mockedInSeparator)
+		return file, true
+	}
+	return path, true
+}
+
+// javaScriptKeywords can't be used as names in esbuild's `Define`.
+var javaScriptKeywords = map[string]bool{
+	"await": true, "break": true, "case": true, "catch": true, "class": true,
+	"const": true, "continue": true, "debugger": true, "default": true,
+	"delete": true, "do": true, "else": true, "enum": true, "export": true,
+	"extends": true, "false": true, "finally": true, "for": true,
+	"function": true, "if": true, "import": true, "in": true,
+	"instanceof": true, "new": true, "null": true, "return": true,
+	"super": true, "switch": true, "this": true, "throw": true, "true": true,
+	"try": true, "typeof": true, "var": true, "void": true, "while": true,
+	"with": true, "yield": true,
+}
+
+// factoryGlobals are available in factories, because the mocked modules
+// provide them.
+var factoryGlobals = map[string]bool{
+	"mockFn": true, "spyOn": true, "require": true, "module": true, "exports": true,
+}
+
+// testFileBindingsIn returns the names in a factory that refer to imports
+// or top-level variables of the test file. Those don't exist when the
+// factory runs. Since esbuild's `Define` only replaces identifiers that
+// aren't bound, it tells which names are free in the factory, and which of
+// those are bound at the top level of the test file.
+func testFileBindingsIn(code string, factory string, loader api.Loader) []string {
+	free, ok := freeIdentifiers("("+factory+");", identifiers(factory), loader)
+	if !ok {
+		return nil
+	}
+	candidates := []string{}
+	for _, name := range free {
+		if !factoryGlobals[name] {
+			candidates = append(candidates, name)
+		}
+	}
+	if len(candidates) == 0 {
+		return nil
+	}
+	unbound, ok := freeIdentifiers(
+		code+"\n;["+strings.Join(candidates, ", ")+"];\n", candidates, loader)
+	if !ok {
+		return nil
+	}
+	bindings := []string{}
+	for _, name := range candidates {
+		if !contains(unbound, name) {
+			bindings = append(bindings, name)
+		}
+	}
+	return bindings
+}
+
+// freeIdentifiers returns the names that are referenced without being bound
+// in the given code.
+func freeIdentifiers(code string, names []string, loader api.Loader) ([]string, bool) {
+	defines := map[string]string{}
+	for i, name := range names {
+		defines[name] = fmt.Sprintf("__strFreeIdentifier%d_", i)
+	}
+	result := api.Transform(code, api.TransformOptions{Loader: loader, Define: defines})
+	if len(result.Errors) > 0 {
+		return nil, false
+	}
+	free := []string{}
+	for _, name := range names {
+		if strings.Contains(string(result.Code), defines[name]) {
+			free = append(free, name)
+		}
+	}
+	return free, true
+}
+
+// identifiers returns the distinct words in the given code that can be
+// identifiers. Non-ASCII words are left out, since they aren't necessarily
+// valid names for `Define`.
+func identifiers(code string) []string {
+	result := []string{}
+	for i := 0; i < len(code); {
+		if !isIdentifierChar(code[i]) {
+			i++
+			continue
+		}
+		start := i
+		for i < len(code) && isIdentifierChar(code[i]) {
+			i++
+		}
+		word := code[start:i]
+		if '0' <= word[0] && word[0] <= '9' || strings.IndexFunc(word, isNonASCII) >= 0 ||
+			javaScriptKeywords[word] || contains(result, word) {
+			continue
+		}
+		result = append(result, word)
+	}
+	return result
+}
+
+func isNonASCII(char rune) bool {
+	return char >= 0x80
+}
+
+// mockMessage returns an error for the `mockModule` call at the given
+// offset, in the format of esbuild's errors.
+func mockMessage(testFile string, code string, offset int, text string) api.Message {
+	lineStart := strings.LastIndexByte(code[:offset], '\n') + 1
+	lineEnd := strings.IndexByte(code[offset:], '\n')
+	if lineEnd < 0 {
+		lineEnd = len(code)
+	} else {
+		lineEnd += offset
+	}
+	file := testFile
+	if workingDirectory, err := os.Getwd(); err == nil {
+		if relative, err := filepath.Rel(workingDirectory, testFile); err == nil {
+			file = relative
+		}
+	}
+	return api.Message{
+		Text: text,
+		Location: &api.Location{
+			File:     file,
+			Line:     strings.Count(code[:offset], "\n") + 1,
+			Column:   offset - lineStart,
+			Length:   len("mockModule"),
+			LineText: strings.TrimRight(code[lineStart:lineEnd], "\r"),
+		},
+	}
+}
+
+// testFileMocks are the `mockModule` calls of a test file, and the errors
+// for the ones that can't be used.
+type testFileMocks struct {
+	mocks    []moduleMock
+	errors   []api.Message
+	reported bool
+}
+
+type moduleMocker struct {
+	config            *Config
+	configuredLoaders map[string]api.Loader
+	lock              sync.Mutex
+	testFiles         map[string]*testFileMocks
+}
+
+func mockModules(config *Config, configuredLoaders map[string]api.Loader) api.Plugin {
+	mocker := &moduleMocker{
+		config:            config,
+		configuredLoaders: configuredLoaders,
+		testFiles:         map[string]*testFileMocks{},
+	}
+	return api.Plugin{
+		Name: "mock modules",
+		Setup: func(build api.PluginBuild) {
+			build.OnResolve(api.OnResolveOptions{Filter: ".*"}, mocker.onResolve)
+			build.OnLoad(api.OnLoadOptions{Filter: ".*", Namespace: mockScopeNamespace},
+				mocker.onLoadScoped)
+			build.OnLoad(api.OnLoadOptions{Filter: ".*", Namespace: mockedModuleNamespace},
+				mocker.onLoadMocked)
+		},
+	}
+}
+
+// parseTestFile finds the `mockModule` calls in the given test file. The
+// results are cached, since esbuild resolves imports concurrently.
+func (mocker *moduleMocker) parseTestFile(testFile string) *testFileMocks {
+	if parsed, ok := mocker.testFiles[testFile]; ok {
+		return parsed
+	}
+	parsed := &testFileMocks{mocks: []moduleMock{}, errors: []api.Message{}}
+	mocker.testFiles[testFile] = parsed
+	if _, ok := loaders[filepath.Ext(testFile)]; !ok || isInNodeModules(testFile) {
+		return parsed
+	}
+	content, err := os.ReadFile(testFile)
+	if err != nil {
+		return parsed
+	}
+	code := string(content)
+	mocks, errors := parseModuleMocks(code)
+	for _, failure := range errors {
+		parsed.errors = append(parsed.errors,
+			mockMessage(testFile, code, failure.offset, failure.message))
+	}
+	if len(mocks) == 0 {
+		return parsed
+	}
+	loader, err := pickLoader(mocker.configuredLoaders, testFile)
+	if err != nil {
+		parsed.mocks = mocks
+		return parsed
+	}
+	for _, mock := range mocks {
+		if names := testFileBindingsIn(code, mock.factory, loader); len(names) > 0 {
+			quoted := []string{}
+			for _, name := range names {
+				quoted = append(quoted, fmt.Sprintf("%q", name))
+			}
+			parsed.errors = append(parsed.errors, mockMessage(testFile, code, mock.offset, fmt.Sprintf(
+				"mockModule: the factory can't use %s from the test file, since it runs before the test file (use mockFn, spyOn or globals instead)",
+				strings.Join(quoted, ", "))))
+			continue
+		}
+		parsed.mocks = append(parsed.mocks, mock)
+	}
+	return parsed
+}
+
+// mocksIn returns the usable `mockModule` calls in the given test file.
+func (mocker *moduleMocker) mocksIn(testFile string) []moduleMock {
+	mocker.lock.Lock()
+	defer mocker.lock.Unlock()
+	return mocker.parseTestFile(testFile).mocks
+}
+
+// unreportedErrors returns the errors for the `mockModule` calls in the
+// given test file, the first time it's called for that file.
+func (mocker *moduleMocker) unreportedErrors(testFile string) []api.Message {
+	mocker.lock.Lock()
+	defer mocker.lock.Unlock()
+	parsed := mocker.parseTestFile(testFile)
+	if parsed.reported {
+		return nil
+	}
+	parsed.reported = true
+	return parsed.errors
+}
+
+func (mocker *moduleMocker) onResolve(args api.OnResolveArgs) (api.OnResolveResult, error) {
+	if result, ok, err := mocker.resolveConfigMock(args); ok || err != nil {
+		return result, err
+	}
+	testFile := ""
+	switch args.Namespace {
+	case "file":
+		testFile = args.Importer
+	case mockScopeNamespace:
+		_, testFile, _ = cut(args.Importer, mockedInSeparator)
+	default:
+		return api.OnResolveResult{}, nil
+	}
+	if errors := mocker.unreportedErrors(testFile); len(errors) > 0 {
+		return api.OnResolveResult{Errors: errors}, nil
+	}
+	mocks := mocker.mocksIn(testFile)
+	if len(mocks) == 0 {
+		return api.OnResolveResult{}, nil
+	}
+	target := mockTarget(args.ResolveDir, args.Path)
+	for _, mock := range mocks {
+		if mockTarget(filepath.Dir(testFile), mock.specifier) == target {
+			return api.OnResolveResult{
+				Path:      testFile + mockSeparator + mock.specifier,
+				Namespace: mockedModuleNamespace,
+			}, nil
+		}
+	}
+	if !strings.HasPrefix(args.Path, ".") {
+		return api.OnResolveResult{}, nil
+	}
+	file, ok := resolveFile(filepath.Join(args.ResolveDir, args.Path))
+	if !ok || isInNodeModules(file) {
+		return api.OnResolveResult{}, nil
+	}
+	if loader, err := pickLoader(mocker.configuredLoaders, file); err != nil || !isSourceLoader(loader) {
+		return api.OnResolveResult{}, nil
+	}
+	return api.OnResolveResult{
+		Path:      file + mockedInSeparator + testFile,
+		Namespace: mockScopeNamespace,
+	}, nil
+}
+
+// resolveConfigMock redirects imports of modules that are mocked in the
+// config, except for imports from the fake modules themselves.
+func (mocker *moduleMocker) resolveConfigMock(args api.OnResolveArgs) (api.OnResolveResult, bool, error) {
+	if len(mocker.config.ModuleMocks) == 0 || args.ResolveDir == "" {
+		return api.OnResolveResult{}, false, nil
+	}
+	workingDirectory, err := os.Getwd()
+	if err != nil {
+		return api.OnResolveResult{}, false, err
+	}
+	target := mockTarget(args.ResolveDir, args.Path)
+	for specifier, fake := range mocker.config.ModuleMocks {
+		if mockTarget(workingDirectory, specifier) != target {
+			continue
+		}
+		fakeFile := filepath.Join(workingDirectory, fake)
+		if resolved, ok := resolveFile(fakeFile); ok {
+			fakeFile = resolved
+		}
+		importer, _, _ := cut(args.Importer, mockedInSeparator)
+		if importer == fakeFile {
+			return api.OnResolveResult{}, false, nil
+		}
+		return api.OnResolveResult{Path: fakeFile}, true, nil
+	}
+	return api.OnResolveResult{}, false, nil
+}
+
+func (mocker *moduleMocker) onLoadScoped(args api.OnLoadArgs) (api.OnLoadResult, error) {
+	file, _, _ := cut(args.Path, mockedInSeparator)
+	loader, err := pickLoader(mocker.configuredLoaders, file)
+	if err != nil {
+		return api.OnLoadResult{}, err
+	}
+	code, err := os.ReadFile(file)
+	if err != nil {
+		return api.OnLoadResult{}, err
+	}
+	contents := injectFileInfo(file, string(code))
+	return api.OnLoadResult{
+		Contents:   &contents,
+		Loader:     loader,
+		ResolveDir: filepath.Dir(file),
+	}, nil
+}
+
+func (mocker *moduleMocker) onLoadMocked(args api.OnLoadArgs) (api.OnLoadResult, error) {
+	testFile, specifier, _ := cut(args.Path, mockSeparator)
+	for _, mock := range mocker.mocksIn(testFile) {
+		if mock.specifier != specifier {
+			continue
+		}
+		loader, err := pickLoader(mocker.configuredLoaders, testFile)
+		if err != nil {
+			return api.OnLoadResult{}, err
+		}
+		contents := fmt.Sprintf(
+			"const { mockFn, spyOn } = require(\"str\");\n%smodule.exports = (%s)();\n",
+			fileInfoRequire(testFile, mock.factory), mock.factory)
+		return api.OnLoadResult{
+			Contents:   &contents,
+			Loader:     loader,
+			ResolveDir: filepath.Dir(testFile),
+		}, nil
+	}
+	return api.OnLoadResult{}, fmt.Errorf("no mock for %s in %s", specifier, testFile)
+}
+
+// fileInfoRequire provides `__dirname` and friends for a factory that was
+// copied out of the given test file.
+func fileInfoRequire(testFile string, factory string) string {
+	names := fileInfoVariables(factory)
+	if len(names) == 0 {
+		return ""
+	}
+	return fmt.Sprintf("const { %s } = require(%s);\n",
+		strings.Join(names, ", "), jsString(fileInfoPrefix+testFile))
+}
@@ -0,0 +1,47 @@
+package cli
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// findProjectRoot returns the nearest directory (starting from the current
+// one) that contains a package.json, or the current directory if there is
+// none. `str` runs in the project root, so that config files, tsconfig
+// files and node_modules are found from any subdirectory.
+func findProjectRoot() (string, error) {
+	workingDirectory, err := os.Getwd()
+	if err != nil {
+		return "", err
+	}
+	dir := workingDirectory
+	for {
+		if _, err := os.Stat(filepath.Join(dir, "package.json")); err == nil {
+			return dir, nil
+		}
+		parent := filepath.Dir(dir)
+		if parent == dir {
+			return workingDirectory, nil
+		}
+		dir = parent
+	}
+}
+
+// rebasePath converts a path relative to the current directory into a path
+// relative to the project root. Paths outside of the project root are
+// made absolute.
+func rebasePath(root string, file string) (string, error) {
+	absolute, err := filepath.Abs(file)
+	if err != nil {
+		return "", err
+	}
+	relative, err := filepath.Rel(root, absolute)
+	if err != nil {
+		return "", err
+	}
+	if relative == ".." || strings.HasPrefix(relative, ".."+string(filepath.Separator)) {
+		return absolute, nil
+	}
+	return relative, nil
+}
@@ -0,0 +1,90 @@
+package cli
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"strings"
+)
+
+type sourceMap struct {
+	Sources  []string `json:"sources"`
+	Mappings string   `json:"mappings"`
+}
+
+// mapping maps a position in the generated file to a line in one of the
+// source files. Lines and columns are zero-based.
+type mapping struct {
+	generatedLine   int
+	generatedColumn int
+	source          int
+	originalLine    int
+}
+
+func readSourceMap(file string) (sourceMap, error) {
+	content, err := os.ReadFile(file)
+	if err != nil {
+		return sourceMap{}, err
+	}
+	result := sourceMap{}
+	err = json.Unmarshal(content, &result)
+	if err != nil {
+		return sourceMap{}, fmt.Errorf("%s: %s", file, err)
+	}
+	return result, nil
+}
+
+func (sourceMap sourceMap) decodeMappings() ([]mapping, error) {
+	result := []mapping{}
+	source, originalLine := 0, 0
+	for generatedLine, line := range strings.Split(sourceMap.Mappings, ";") {
+		generatedColumn := 0
+		for _, segment := range strings.Split(line, ",") {
+			if segment == "" {
+				continue
+			}
+			fields, err := decodeVLQs(segment)
+			if err != nil {
+				return nil, err
+			}
+			generatedColumn += fields[0]
+			if len(fields) < 4 {
+				continue
+			}
+			source += fields[1]
+			originalLine += fields[2]
+			result = append(result, mapping{
+				generatedLine:   generatedLine,
+				generatedColumn: generatedColumn,
+				source:          source,
+				originalLine:    originalLine,
+			})
+		}
+	}
+	return result, nil
+}
+
+const base64Digits = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+
+func decodeVLQs(segment string) ([]int, error) {
+	values := []int{}
+	value, shift := 0, 0
+	for _, char := range segment {
+		digit := strings.IndexRune(base64Digits, char)
+		if digit < 0 {
+			return nil, fmt.Errorf("invalid source map segment: %s", segment)
+		}
+		value += (digit & 31) << shift
+		if digit&32 != 0 {
+			shift += 5
+			continue
+		}
+		if value&1 != 0 {
+			values = append(values, -(value >> 1))
+		} else {
+			values = append(values, value>>1)
+		}
+		value, shift = 0, 0
+	}
+	return values, nil
+}
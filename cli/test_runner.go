@@ -1,38 +1,116 @@
 package cli
 
 import (
+	"encoding/json"
 	"fmt"
 	"os"
 	"os/exec"
+	"path/filepath"
+	"strings"
 	"syscall"
 
 	"github.com/lithammer/dedent"
 )
 
-func runnerCode(testFiles []string) string {
-	code := `
+type runnerOptions struct {
+	Timeout     int               `json:"timeout,omitempty"`
+	Capture     bool              `json:"capture,omitempty"`
+	Slow        int               `json:"slow,omitempty"`
+	Filter      string            `json:"filter,omitempty"`
+	Root        string            `json:"root,omitempty"`
+	Seed        *uint32           `json:"seed,omitempty"`
+	Reporters   map[string]string `json:"reporters,omitempty"`
+	Parallelism int               `json:"parallelism,omitempty"`
+}
+
+func runnerCode(config *Config, testFiles []string) string {
+	timeout := config.Timeout
+	parallelism := config.Parallelism
+	if config.Inspect != "" {
+		// Pausing in the debugger shouldn't make tests time out, and the
+		// debugger can only attach to one process.
+		timeout = 0
+		parallelism = 0
+	}
+	root := ""
+	// Source locations are reported relative to the project root, but the
+	// tests run in the directory `str` was started in.
+	if workingDirectory, err := os.Getwd(); err == nil && workingDirectory != config.WorkingDirectory {
+		root = workingDirectory
+	}
+	// Report files are relative to the project root as well.
+	var reporters map[string]string
+	for reporter, file := range config.Reporters {
+		if reporters == nil {
+			reporters = map[string]string{}
+		}
+		absolute, err := filepath.Abs(file)
+		if err != nil {
+			absolute = file
+		}
+		reporters[reporter] = absolute
+	}
+	options, _ := json.Marshal(runnerOptions{
+		Timeout:     timeout,
+		Capture:     config.Capture,
+		Slow:        config.Slow,
+		Filter:      config.Filter,
+		Root:        root,
+		Seed:        config.Seed,
+		Reporters:   reporters,
+		Parallelism: parallelism,
+	})
+	code := fmt.Sprintf(`
 		import { _strTestRunner, describe } from "str";
-		async function main() {`
+		async function main() {
+			_strTestRunner.configure(%s);`,
+		options)
+	if config.GlobalSetup != "" {
+		code += fmt.Sprintf(`
+			await _strTestRunner.runGlobalSetup(() => import(%s));`,
+			importPath(config.GlobalSetup))
+	}
+	for _, setupFile := range config.SetupFiles {
+		code += fmt.Sprintf(`
+			await _strTestRunner.runSetupFile(() => import(%s));`,
+			importPath(setupFile))
+	}
 	for _, testFile := range testFiles {
 		code += fmt.Sprintf(`
-			await _strTestRunner.enterTestFile("%s", () => import("./%s"));`,
-			testFile, testFile)
+			await _strTestRunner.enterTestFile(%s, () => import(%s));`,
+			jsString(testFile), importPath(testFile))
 	}
-	code += `
-			await _strTestRunner.runTests();
+	globalTeardown := ""
+	if config.GlobalTeardown != "" {
+		globalTeardown = fmt.Sprintf("() => import(%s)", importPath(config.GlobalTeardown))
+	}
+	code += fmt.Sprintf(`
+			await _strTestRunner.runTests(%s);
 		}
 		main();
-	`
+	`, globalTeardown)
 	return dedent.Dedent(code)
 }
 
+func importPath(file string) string {
+	if filepath.IsAbs(file) {
+		return jsString(filepath.ToSlash(file))
+	}
+	return jsString("./" + filepath.ToSlash(file))
+}
+
 type runner struct {
+	config *Config
 	failed bool
 }
 
-func Run(testFiles []string) (int, error) {
-	runner := runner{}
-	err := runner.runTestFiles(testFiles)
+func Run(arguments []string) (int, error) {
+	config, testFiles, err := loadSettings(arguments)
+	if err != nil {
+		return 1, err
+	}
+	runner := runner{config: config}
+	err = runner.runTestFiles(testFiles)
 	if err != nil {
 		return 1, err
 	}
@@ -44,18 +122,149 @@ func Run(testFiles []string) (int, error) {
 }
 
 func (runner *runner) runTestFiles(testFiles []string) error {
-	strDistDir, err := os.MkdirTemp("", "str-bundle")
+	code := runnerCode(runner.config, testFiles)
+	if runner.config.PrintRunner {
+		fmt.Print(code)
+		return nil
+	}
+	if runner.config.Typecheck {
+		files := append(append([]string{}, runner.config.SetupFiles...), testFiles...)
+		err := typecheck(runner.config, files)
+		if err != nil {
+			return err
+		}
+	}
+	bundleFile, cleanup, err := runner.prepareBundle(code)
 	if err != nil {
 		return err
 	}
-	defer os.RemoveAll(strDistDir)
-	os.Mkdir(strDistDir, 0755)
-	bundleFile := strDistDir + "/main.js"
-	err = bundle(runnerCode(testFiles), bundleFile)
+	defer cleanup()
+	v8CoverageDir := ""
+	if runner.config.coverageEnabled() {
+		v8CoverageDir, err = os.MkdirTemp("", "str-coverage")
+		if err != nil {
+			return err
+		}
+		defer os.RemoveAll(v8CoverageDir)
+	}
+	err = runner.runBundle(bundleFile, v8CoverageDir)
 	if err != nil {
 		return err
 	}
-	return runner.runBundle(bundleFile)
+	if v8CoverageDir != "" {
+		return runner.reportCoverage(v8CoverageDir, bundleFile, testFiles)
+	}
+	return nil
+}
+
+// prepareBundle bundles the runner code and returns the bundle file and a
+// function that cleans up afterwards. The bundle is written into the
+// --keep-bundle directory, the bundle cache or a temporary directory.
+func (runner *runner) prepareBundle(code string) (string, func(), error) {
+	noCleanup := func() {}
+	if runner.config.KeepBundle != "" {
+		err := checkEsmBundleDir(runner.config, "--keep-bundle", runner.config.KeepBundle)
+		if err != nil {
+			return "", nil, err
+		}
+		dir, err := filepath.Abs(runner.config.KeepBundle)
+		if err != nil {
+			return "", nil, err
+		}
+		err = os.MkdirAll(dir, 0755)
+		if err != nil {
+			return "", nil, err
+		}
+		bundleFile := filepath.Join(dir, runner.config.bundleFileName())
+		metafile, err := bundle(runner.config, code, bundleFile)
+		if err != nil {
+			return "", nil, err
+		}
+		err = writeFile(filepath.Join(dir, "meta.json"), metafile)
+		if err != nil {
+			return "", nil, err
+		}
+		fmt.Fprintf(os.Stderr, "Bundle written to %s\n",
+			filepath.Join(runner.config.KeepBundle, runner.config.bundleFileName()))
+		return bundleFile, noCleanup, nil
+	}
+	if cacheDir, ok := runner.config.cacheDir(); ok {
+		err := checkEsmBundleDir(runner.config, "cacheDir", cacheDir)
+		if err != nil {
+			return "", nil, err
+		}
+		bundleFile, err := cachedBundle(cacheDir, runner.config, code)
+		return bundleFile, noCleanup, err
+	}
+	dir, err := bundleTempDir(runner.config)
+	if err != nil {
+		return "", nil, err
+	}
+	cleanup := func() { os.RemoveAll(dir) }
+	bundleFile := filepath.Join(dir, runner.config.bundleFileName())
+	_, err = bundle(runner.config, code, bundleFile)
+	if err != nil {
+		cleanup()
+		return "", nil, err
+	}
+	return bundleFile, cleanup, nil
+}
+
+// checkEsmBundleDir makes sure that a bundle in the given directory can
+// import from the project's node_modules. ES modules don't support
+// NODE_PATH, so node only finds them if the bundle lives inside the
+// project.
+func checkEsmBundleDir(config *Config, option string, dir string) error {
+	if !config.esm() {
+		return nil
+	}
+	root, err := os.Getwd()
+	if err != nil {
+		return err
+	}
+	absolute, err := filepath.Abs(dir)
+	if err != nil {
+		return err
+	}
+	if !isInsideDir(absolute, root) {
+		return configErrorf("%s: with the esm format, the bundle has to be inside the project, so that node can find node_modules: %s", option, dir)
+	}
+	return nil
+}
+
+// bundleTempDir creates a temporary directory for the bundle. For ES
+// modules, it's created in the nearest node_modules directory (up to the
+// workspace root), since node only finds imports from the parents of the
+// bundle.
+func bundleTempDir(config *Config) (string, error) {
+	if !config.esm() {
+		return os.MkdirTemp("", "str-bundle")
+	}
+	dir, err := os.Getwd()
+	if err != nil {
+		return "", err
+	}
+	for {
+		nodeModules := filepath.Join(dir, "node_modules")
+		if info, err := os.Stat(nodeModules); err == nil && info.IsDir() {
+			parent := filepath.Join(nodeModules, ".cache", "str")
+			err = os.MkdirAll(parent, 0755)
+			if err != nil {
+				return "", err
+			}
+			return os.MkdirTemp(parent, "bundle-")
+		}
+		if config.Workspace.root == "" || !isInsideDir(dir, config.Workspace.root) || dir == config.Workspace.root {
+			return os.MkdirTemp("", "str-bundle")
+		}
+		dir = filepath.Dir(dir)
+	}
+}
+
+// isInsideDir checks whether the absolute path is the given directory or
+// inside of it.
+func isInsideDir(path string, dir string) bool {
+	return path == dir || strings.HasPrefix(path, dir+string(filepath.Separator))
 }
 
 func writeFile(file string, content string) error {
@@ -71,16 +280,32 @@ func writeFile(file string, content string) error {
 	return nil
 }
 
-func (runner *runner) runBundle(bundleFile string) error {
-	command := exec.Command("node", bundleFile)
-	workingDirectory, err := os.Getwd()
+func (runner *runner) runBundle(bundleFile string, v8CoverageDir string) error {
+	nodeArgs := []string{"--enable-source-maps", bundleFile}
+	if runner.config.Inspect != "" {
+		nodeArgs = append([]string{runner.config.Inspect}, nodeArgs...)
+	}
+	command := exec.Command("node", nodeArgs...)
+	// `str` itself runs in the project root, but the tests run in the
+	// directory `str` was started in.
+	command.Dir = runner.config.WorkingDirectory
+	nodePath, err := runner.config.Workspace.nodePath()
+	if err != nil {
+		return err
+	}
+	command.Env = os.Environ()
+	envFileVariables, err := readEnvFiles(runner.config.EnvFiles)
 	if err != nil {
 		return err
 	}
-	command.Env = append(
-		os.Environ(),
-		fmt.Sprintf("NODE_PATH=%s/node_modules", workingDirectory),
-	)
+	command.Env = append(command.Env, envFileVariables...)
+	for key, value := range runner.config.Env {
+		command.Env = append(command.Env, fmt.Sprintf("%s=%s", key, value))
+	}
+	command.Env = append(command.Env, nodePath)
+	if v8CoverageDir != "" {
+		command.Env = append(command.Env, fmt.Sprintf("NODE_V8_COVERAGE=%s", v8CoverageDir))
+	}
 	command.Stdout = os.Stdout
 	command.Stderr = os.Stderr
 	err = command.Run()
@@ -0,0 +1,289 @@
+package cli
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// tsconfig holds the parts of a `tsconfig.json` that `str` needs to know
+// about itself. esbuild reads everything else (e.g. `jsxFactory`) on its
+// own. `pathsBase` is the absolute directory that `paths` are resolved
+// from and `files` are the absolute paths of the tsconfig and the files it
+// extends.
+type tsconfig struct {
+	file            string
+	files           []string
+	pathsBase       string
+	baseUrl         string
+	paths           map[string][]string
+	target          string
+	jsx             string
+	jsxImportSource string
+}
+
+type tsconfigJson struct {
+	Extends         string                  `json:"extends"`
+	CompilerOptions tsconfigCompilerOptions `json:"compilerOptions"`
+}
+
+type tsconfigCompilerOptions struct {
+	BaseUrl         *string             `json:"baseUrl"`
+	Paths           map[string][]string `json:"paths"`
+	Target          string              `json:"target"`
+	Jsx             string              `json:"jsx"`
+	JsxImportSource string              `json:"jsxImportSource"`
+}
+
+// tsconfigFile returns the tsconfig file given in the config, or the
+// `tsconfig.json` in the current directory if it exists.
+func tsconfigFile(config *Config) (string, bool) {
+	if config.Esbuild.Tsconfig != "" {
+		return config.Esbuild.Tsconfig, true
+	}
+	if _, err := os.Stat("tsconfig.json"); err == nil {
+		return "tsconfig.json", true
+	}
+	return "", false
+}
+
+func loadTsconfig(config *Config) (*tsconfig, error) {
+	file, ok := tsconfigFile(config)
+	if !ok {
+		return &tsconfig{}, nil
+	}
+	absolute, err := filepath.Abs(file)
+	if err != nil {
+		return nil, err
+	}
+	result := &tsconfig{file: file}
+	err = result.read(absolute, 0)
+	if err != nil {
+		return nil, err
+	}
+	return result, nil
+}
+
+// read reads the given tsconfig file, after reading the files it extends.
+// Settings from later files override earlier ones.
+func (result *tsconfig) read(file string, depth int) error {
+	if depth > 10 {
+		return configErrorf("%s: too many nested \"extends\"", result.file)
+	}
+	content, err := os.ReadFile(file)
+	if err != nil {
+		return configErrorf("%s", err)
+	}
+	parsed := tsconfigJson{}
+	err = json.Unmarshal(stripJsonComments(content), &parsed)
+	if err != nil {
+		return configErrorf("%s: %s", file, err)
+	}
+	result.files = append(result.files, file)
+	dir := filepath.Dir(file)
+	// `extends` can also refer to packages. We only follow files, but still
+	// keep track of the package's tsconfig, since esbuild reads it.
+	if parsed.Extends != "" && !strings.HasPrefix(parsed.Extends, ".") && !filepath.IsAbs(parsed.Extends) {
+		if extended, ok := packageTsconfig(dir, parsed.Extends); ok {
+			result.files = append(result.files, extended)
+		}
+	}
+	if strings.HasPrefix(parsed.Extends, ".") || filepath.IsAbs(parsed.Extends) {
+		extended := parsed.Extends
+		if !filepath.IsAbs(extended) {
+			extended = filepath.Join(dir, extended)
+		}
+		if filepath.Ext(extended) != ".json" {
+			extended += ".json"
+		}
+		err = result.read(extended, depth+1)
+		if err != nil {
+			return err
+		}
+	}
+	options := parsed.CompilerOptions
+	if options.BaseUrl != nil {
+		result.baseUrl = filepath.Join(dir, *options.BaseUrl)
+		result.pathsBase = result.baseUrl
+	}
+	if options.Paths != nil {
+		result.paths = options.Paths
+		if options.BaseUrl == nil && result.baseUrl == "" {
+			result.pathsBase = dir
+		}
+	}
+	if options.Target != "" {
+		result.target = options.Target
+	}
+	if options.Jsx != "" {
+		result.jsx = options.Jsx
+	}
+	if options.JsxImportSource != "" {
+		result.jsxImportSource = options.JsxImportSource
+	}
+	return nil
+}
+
+// packageTsconfig finds the tsconfig file of a package that `extends`
+// refers to in the node_modules directories of `dir` and its parents.
+func packageTsconfig(dir string, extends string) (string, bool) {
+	for {
+		base := filepath.Join(dir, "node_modules", extends)
+		for _, candidate := range []string{base, base + ".json", filepath.Join(base, "tsconfig.json")} {
+			if info, err := os.Stat(candidate); err == nil && !info.IsDir() {
+				return candidate, true
+			}
+		}
+		parent := filepath.Dir(dir)
+		if parent == dir {
+			return "", false
+		}
+		dir = parent
+	}
+}
+
+// resolves checks whether the given non-relative import resolves to a
+// file through `paths` or `baseUrl`.
+func (tsconfig *tsconfig) resolves(importPath string) bool {
+	for pattern, substitutions := range tsconfig.paths {
+		wildcard, ok := matchPathPattern(pattern, importPath)
+		if !ok {
+			continue
+		}
+		for _, substitution := range substitutions {
+			candidate := strings.Replace(substitution, "*", wildcard, 1)
+			if fileExists(filepath.Join(tsconfig.pathsBase, candidate)) {
+				return true
+			}
+		}
+	}
+	if tsconfig.baseUrl != "" {
+		return fileExists(filepath.Join(tsconfig.baseUrl, importPath))
+	}
+	return false
+}
+
+// matchPathPattern matches patterns from `paths` like `@app/*` and returns
+// the part matched by the wildcard.
+func matchPathPattern(pattern string, importPath string) (string, bool) {
+	prefix, suffix, hasWildcard := cut(pattern, "*")
+	if !hasWildcard {
+		return "", pattern == importPath
+	}
+	if len(importPath) < len(prefix)+len(suffix) ||
+		!strings.HasPrefix(importPath, prefix) ||
+		!strings.HasSuffix(importPath, suffix) {
+		return "", false
+	}
+	return importPath[len(prefix) : len(importPath)-len(suffix)], true
+}
+
+var resolveExtensions = []string{".ts", ".tsx", ".js", ".jsx", ".json"}
+
+// fileExists checks whether a module could be loaded from the given path,
+// trying the same extensions and index files as esbuild.
+func fileExists(path string) bool {
+	_, ok := resolveFile(path)
+	return ok
+}
+
+// resolveFile returns the file that a module would be loaded from.
+func resolveFile(path string) (string, bool) {
+	candidates := []string{path}
+	for _, extension := range resolveExtensions {
+		candidates = append(candidates, path+extension)
+	}
+	for _, extension := range resolveExtensions {
+		candidates = append(candidates, filepath.Join(path, "index"+extension))
+	}
+	for _, candidate := range candidates {
+		if info, err := os.Stat(candidate); err == nil && !info.IsDir() {
+			return candidate, true
+		}
+	}
+	return "", false
+}
+
+// stripJsonComments removes comments and trailing commas, which are
+// allowed in tsconfig files.
+func stripJsonComments(content []byte) []byte {
+	result := []byte{}
+	for i := 0; i < len(content); i++ {
+		char := content[i]
+		switch {
+		case char == '"':
+			start := i
+			for i++; i < len(content) && content[i] != '"'; i++ {
+				if content[i] == '\\' {
+					i++
+				}
+			}
+			end := i + 1
+			if end > len(content) {
+				end = len(content)
+			}
+			result = append(result, content[start:end]...)
+		case char == '/' && i+1 < len(content) && content[i+1] == '/':
+			for i < len(content) && content[i] != '\n' {
+				i++
+			}
+			result = append(result, '\n')
+		case char == '/' && i+1 < len(content) && content[i+1] == '*':
+			i += 2
+			for i+1 < len(content) && !(content[i] == '*' && content[i+1] == '/') {
+				i++
+			}
+			i++
+		case char == ',':
+			next := i + 1
+			for next < len(content) && strings.ContainsRune(" \t\r\n", rune(content[next])) {
+				next++
+			}
+			if next < len(content) && (content[next] == '}' || content[next] == ']') {
+				continue
+			}
+			result = append(result, char)
+		default:
+			result = append(result, char)
+		}
+	}
+	return result
+}
+
+// jsxRuntime returns the code of a module that provides the automatic JSX
+// runtime (`"jsx": "react-jsx"` or `"react-jsxdev"`) through the classic
+// `createElement` interface, since esbuild only supports the latter. Other
+// modes (e.g. "preserve") can't be run by node, so they're transformed
+// like "react".
+func (tsconfig *tsconfig) jsxRuntime() (string, bool) {
+	source := tsconfig.jsxImportSource
+	if source == "" {
+		source = "react"
+	}
+	switch strings.ToLower(tsconfig.jsx) {
+	case "react-jsx":
+		return fmt.Sprintf(`import { jsx, jsxs, Fragment } from %s;
+export const %s = Fragment;
+export function %s(type, config, ...children) {
+  const { key, ...props } = config || {};
+  if (children.length === 1) props.children = children[0];
+  if (children.length > 1) props.children = children;
+  return (children.length > 1 ? jsxs : jsx)(type, props, key);
+}
+`, jsString(source+"/jsx-runtime"), jsxFragment, jsxFactory), true
+	case "react-jsxdev":
+		return fmt.Sprintf(`import { jsxDEV, Fragment } from %s;
+export const %s = Fragment;
+export function %s(type, config, ...children) {
+  const { key, ...props } = config || {};
+  if (children.length === 1) props.children = children[0];
+  if (children.length > 1) props.children = children;
+  return jsxDEV(type, props, key, children.length > 1);
+}
+`, jsString(source+"/jsx-dev-runtime"), jsxFragment, jsxFactory), true
+	default:
+		return "", false
+	}
+}
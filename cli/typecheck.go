@@ -0,0 +1,145 @@
+package cli
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"os/exec"
+	"path/filepath"
+	"regexp"
+	"strconv"
+	"strings"
+
+	"github.com/evanw/esbuild/pkg/api"
+)
+
+var tscError = regexp.MustCompile(`^(.+)\((\d+),(\d+)\): error (TS\d+): (.*)$`)
+
+// typecheck runs the project's `tsc` on the given files and prints type
+// errors in the same format as esbuild's errors.
+func typecheck(config *Config, files []string) error {
+	tsc := filepath.Join("node_modules", ".bin", "tsc")
+	if _, err := os.Stat(tsc); err != nil {
+		return configErrorf("--typecheck: %s not found, please install typescript", tsc)
+	}
+	// Putting the tsconfig into node_modules makes tsc find the types in
+	// node_modules/@types.
+	cacheDir := filepath.Join("node_modules", ".cache", "str")
+	err := os.MkdirAll(cacheDir, 0755)
+	if err != nil {
+		return err
+	}
+	dir, err := os.MkdirTemp(cacheDir, "typecheck-")
+	if err != nil {
+		return err
+	}
+	defer os.RemoveAll(dir)
+	tsconfigJson, err := typecheckTsconfig(config, files)
+	if err != nil {
+		return err
+	}
+	tsconfigFile := filepath.Join(dir, "tsconfig.json")
+	err = writeFile(tsconfigFile, tsconfigJson)
+	if err != nil {
+		return err
+	}
+	command := exec.Command(tsc, "--project", tsconfigFile, "--pretty", "false")
+	command.Stderr = os.Stderr
+	output, err := command.Output()
+	if err == nil {
+		return nil
+	}
+	if _, ok := err.(*exec.ExitError); !ok {
+		return err
+	}
+	messages := parseTscOutput(string(output))
+	if len(messages) == 0 {
+		fmt.Fprint(os.Stderr, string(output))
+		return &BundleError{}
+	}
+	formattedErrors := api.FormatMessages(messages, api.FormatMessagesOptions{
+		Color: true,
+	})
+	for _, error := range formattedErrors {
+		print(error)
+	}
+	return &BundleError{}
+}
+
+// typecheckTsconfig returns a tsconfig that extends the project's tsconfig
+// and only includes the given files (and what they import).
+func typecheckTsconfig(config *Config, files []string) (string, error) {
+	absoluteFiles := []string{}
+	for _, file := range files {
+		absolute, err := filepath.Abs(file)
+		if err != nil {
+			return "", err
+		}
+		absoluteFiles = append(absoluteFiles, absolute)
+	}
+	compilerOptions := map[string]interface{}{
+		"noEmit": true,
+	}
+	result := map[string]interface{}{
+		"files":           absoluteFiles,
+		"include":         []string{},
+		"compilerOptions": compilerOptions,
+	}
+	if file, ok := tsconfigFile(config); ok {
+		absolute, err := filepath.Abs(file)
+		if err != nil {
+			return "", err
+		}
+		result["extends"] = absolute
+	} else {
+		compilerOptions["target"] = "es2019"
+		compilerOptions["module"] = "commonjs"
+		compilerOptions["moduleResolution"] = "node"
+		compilerOptions["esModuleInterop"] = true
+		compilerOptions["jsx"] = "react"
+		compilerOptions["skipLibCheck"] = true
+	}
+	encoded, err := json.Marshal(result)
+	if err != nil {
+		return "", err
+	}
+	return string(encoded), nil
+}
+
+// parseTscOutput parses errors from `tsc --pretty false`, e.g.
+// `index.test.ts(3,7): error TS2322: Type 'string' is not assignable to
+// type 'number'.` Indented lines continue the previous message.
+func parseTscOutput(output string) []api.Message {
+	messages := []api.Message{}
+	for _, line := range strings.Split(output, "\n") {
+		if match := tscError.FindStringSubmatch(line); match != nil {
+			lineNumber, _ := strconv.Atoi(match[2])
+			column, _ := strconv.Atoi(match[3])
+			messages = append(messages, api.Message{
+				Text: fmt.Sprintf("%s [%s]", match[5], match[4]),
+				Location: &api.Location{
+					File:     match[1],
+					Line:     lineNumber,
+					Column:   column - 1,
+					LineText: sourceLine(match[1], lineNumber),
+				},
+			})
+		} else if strings.HasPrefix(line, " ") && len(messages) > 0 {
+			last := &messages[len(messages)-1]
+			last.Text += "\n" + strings.TrimSpace(line)
+		}
+	}
+	return messages
+}
+
+func sourceLine(file string, line int) string {
+	content, err := os.ReadFile(file)
+	if err != nil {
+		return ""
+	}
+	lines := strings.Split(string(content), "\n")
+	if line < 1 || line > len(lines) {
+		return ""
+	}
+	return strings.TrimRight(lines[line-1], "\r")
+}
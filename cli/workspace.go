@@ -0,0 +1,155 @@
+package cli
+
+import (
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+)
+
+// workspace is a yarn or npm workspace that the current directory belongs
+// to. `root` is the absolute directory of the package.json with the
+// `workspaces` field and `packages` maps package names to their absolute
+// directories.
+type workspace struct {
+	root     string
+	packages map[string]string
+}
+
+// findWorkspace searches the current directory and its parents for a
+// package.json with a `workspaces` field. If there is none, it returns an
+// empty workspace.
+func findWorkspace() (*workspace, error) {
+	dir, err := os.Getwd()
+	if err != nil {
+		return nil, err
+	}
+	for {
+		patterns, ok := readWorkspacePatterns(filepath.Join(dir, "package.json"))
+		if ok {
+			return loadWorkspace(dir, patterns)
+		}
+		parent := filepath.Dir(dir)
+		if parent == dir {
+			return &workspace{}, nil
+		}
+		dir = parent
+	}
+}
+
+// readWorkspacePatterns reads the `workspaces` field, which is either a
+// list of globs or an object with a `packages` field.
+func readWorkspacePatterns(file string) ([]string, bool) {
+	content, err := os.ReadFile(file)
+	if err != nil {
+		return nil, false
+	}
+	var packageJson struct {
+		Workspaces json.RawMessage `json:"workspaces"`
+	}
+	if json.Unmarshal(content, &packageJson) != nil || packageJson.Workspaces == nil {
+		return nil, false
+	}
+	var patterns []string
+	if json.Unmarshal(packageJson.Workspaces, &patterns) == nil {
+		return patterns, true
+	}
+	var object struct {
+		Packages []string `json:"packages"`
+	}
+	if json.Unmarshal(packageJson.Workspaces, &object) == nil {
+		return object.Packages, true
+	}
+	return nil, false
+}
+
+func loadWorkspace(root string, patterns []string) (*workspace, error) {
+	packageJsonPatterns := []string{}
+	for _, pattern := range patterns {
+		packageJsonPatterns = append(packageJsonPatterns, pattern+"/package.json")
+	}
+	packageJsons, err := expandGlobs(root, packageJsonPatterns, nil)
+	if err != nil {
+		return nil, err
+	}
+	result := &workspace{root: root, packages: map[string]string{}}
+	for _, packageJson := range packageJsons {
+		file := filepath.Join(root, packageJson)
+		content, err := os.ReadFile(file)
+		if err != nil {
+			return nil, err
+		}
+		var fields struct {
+			Name string `json:"name"`
+		}
+		if json.Unmarshal(content, &fields) != nil {
+			return nil, configErrorf("%s: invalid package.json", file)
+		}
+		if fields.Name != "" {
+			result.packages[fields.Name] = filepath.Dir(file)
+		}
+	}
+	return result, nil
+}
+
+// contains checks whether the given import refers to a workspace package,
+// e.g. `@org/b` or `@org/b/utils`.
+func (workspace *workspace) contains(importPath string) bool {
+	for name := range workspace.packages {
+		if importPath == name || strings.HasPrefix(importPath, name+"/") {
+			return true
+		}
+	}
+	return false
+}
+
+// nodeModulesDirs returns the node_modules directories that node should
+// load external imports from: the one in the current directory, the ones
+// in its parents up to the workspace root and the ones in the workspace
+// packages.
+func (workspace *workspace) nodeModulesDirs() ([]string, error) {
+	dir, err := os.Getwd()
+	if err != nil {
+		return nil, err
+	}
+	result := []string{filepath.Join(dir, "node_modules")}
+	if workspace.root == "" {
+		return result, nil
+	}
+	for dir != workspace.root && strings.HasPrefix(dir, workspace.root) {
+		dir = filepath.Dir(dir)
+		result = append(result, filepath.Join(dir, "node_modules"))
+	}
+	packageDirs := []string{}
+	for _, packageDir := range workspace.packages {
+		packageDirs = append(packageDirs, packageDir)
+	}
+	sort.Strings(packageDirs)
+	for _, packageDir := range packageDirs {
+		nodeModules := filepath.Join(packageDir, "node_modules")
+		if info, err := os.Stat(nodeModules); err == nil && info.IsDir() && !contains(result, nodeModules) {
+			result = append(result, nodeModules)
+		}
+	}
+	return result, nil
+}
+
+// nodePath returns the `NODE_PATH` environment variable that lets node
+// find the external imports of a bundle.
+func (workspace *workspace) nodePath() (string, error) {
+	dirs, err := workspace.nodeModulesDirs()
+	if err != nil {
+		return "", err
+	}
+	return "NODE_PATH=" + strings.Join(dirs, string(os.PathListSeparator)), nil
+}
+
+func contains(list []string, element string) bool {
+	for _, e := range list {
+		if e == element {
+			return true
+		}
+	}
+	return false
+}
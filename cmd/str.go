@@ -1,17 +1,20 @@
 package main
 
 import (
+	"fmt"
 	"os"
 
 	"github.com/soenkehahn/str/cli"
 )
 
 func main() {
-	testFiles := os.Args[1:]
-	exitCode, err := cli.Run(testFiles)
+	exitCode, err := cli.Run(os.Args[1:])
 	if err != nil {
 		if _, ok := err.(*cli.BundleError); ok {
 			os.Exit(1)
+		} else if _, ok := err.(*cli.ConfigError); ok {
+			fmt.Fprintf(os.Stderr, "error: %s\n", err)
+			os.Exit(1)
 		} else {
 			panic(err)
 		}
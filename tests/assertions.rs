@@ -70,6 +70,14 @@ fn describe_bigger() -> Result<()> {
             false
             index.test.ts -> fails FAILED
             Ran 4 tests, 3 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> fails
+                true
+                    !==
+                false
+                at index.test.ts:17:17
         "#,
     )?;
     Ok(())
@@ -104,6 +112,14 @@ fn describe_pops_description_stack_correctly_after_failures() -> Result<()> {
             index.test.ts -> works ...
             index.test.ts -> works PASSED
             Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> description -> fails
+                true
+                    !==
+                false
+                at index.test.ts:5:21
         "#,
     )?;
     Ok(())
@@ -754,6 +770,11 @@ fn test_alias() -> Result<()> {
             EXCEPTION: foo
             index.test.ts -> fails FAILED
             Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> fails
+                EXCEPTION: foo
         "#,
     )?;
     Ok(())
@@ -784,3 +805,83 @@ fn xit() -> Result<()> {
     )?;
     Ok(())
 }
+
+#[test]
+fn failing_tests_that_fail_are_reported_as_xfail() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            it("works", () => {});
+            it.failing("known bug", () => {
+                assertEq(1, 2);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            index.test.ts -> known bug ...
+            index.test.ts -> known bug XFAIL
+            Ran 2 tests, 1 passed, 0 failed, 1 xfail.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn failing_tests_that_pass_fail() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it.failing("fixed bug", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        1,
+        r#"
+            index.test.ts -> fixed bug ...
+            expected test to fail, but it passed
+            index.test.ts -> fixed bug FAILED (unexpectedly passed)
+            Ran 1 test, 0 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> fixed bug
+                expected test to fail, but it passed
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn failing_tests_can_be_async() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it.failing("rejects", async () => {
+                await null;
+                throw new Error("known bug");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> rejects ...
+            index.test.ts -> rejects XFAIL
+            Ran 1 test, 0 passed, 0 failed, 1 xfail.
+        "#,
+    )?;
+    Ok(())
+}
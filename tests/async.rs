@@ -1,7 +1,7 @@
 mod common;
 
 use anyhow::Result;
-use common::Context;
+use common::{assert_contains, Context};
 
 #[test]
 fn it_async() -> Result<()> {
@@ -52,6 +52,14 @@ fn it_with_async_failure() -> Result<()> {
             false
             index.test.ts -> fails FAILED
             Ran 1 test, 0 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> fails
+                true
+                    !==
+                false
+                at index.test.ts:5:17
         "#,
     )?;
     Ok(())
@@ -143,3 +151,203 @@ fn before_all_async() -> Result<()> {
     )?;
     Ok(())
 }
+
+#[test]
+fn fake_timers_make_long_timeouts_complete_instantly() -> Result<()> {
+    let context = Context::new()?;
+    context.write("str.config.json", r#"{ "timeout": 1000 }"#)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, useFakeTimers, advanceTimersBy } from "str";
+            it("waits for an hour", async () => {
+                useFakeTimers();
+                const oneHour = 60 * 60 * 1000;
+                const done = new Promise((resolve) => setTimeout(resolve, oneHour));
+                advanceTimersBy(oneHour - 1);
+                console.error("almost");
+                advanceTimersBy(1);
+                await done;
+                console.error("done");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> waits for an hour ...
+            almost
+            done
+            index.test.ts -> waits for an hour PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn advance_timers_by_runs_intervals() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq, useFakeTimers, advanceTimersBy } from "str";
+            it("works", () => {
+                useFakeTimers();
+                let ticks = 0;
+                const interval = setInterval(() => ticks++, 100);
+                advanceTimersBy(350);
+                assertEq(ticks, 3);
+                clearInterval(interval);
+                advanceTimersBy(1000);
+                assertEq(ticks, 3);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn run_all_timers() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, useFakeTimers, runAllTimers } from "str";
+            it("works", () => {
+                useFakeTimers();
+                setTimeout(() => console.error("b"), 2000);
+                setTimeout(() => {
+                    console.error("a");
+                    setTimeout(() => console.error("c"), 5000);
+                }, 1000);
+                runAllTimers();
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> works ...
+            a
+            b
+            c
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn run_all_timers_aborts_on_infinite_intervals() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, useFakeTimers, runAllTimers } from "str";
+            it("fails", () => {
+                useFakeTimers();
+                setInterval(() => {}, 100);
+                runAllTimers();
+            });
+        "#,
+    )?;
+    let stderr = context.run_assert_stderr("index.test.ts", 1);
+    assert_contains(
+        stderr,
+        "EXCEPTION: Error: runAllTimers: aborting after 10000 timers",
+    );
+    Ok(())
+}
+
+#[test]
+fn set_system_time() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq, useFakeTimers, setSystemTime, advanceTimersBy } from "str";
+            it("works", () => {
+                useFakeTimers();
+                setSystemTime(new Date("2020-01-01T00:00:00.000Z"));
+                assertEq(new Date().toISOString(), "2020-01-01T00:00:00.000Z");
+                advanceTimersBy(1500);
+                assertEq(Date.now(), Date.parse("2020-01-01T00:00:01.500Z"));
+                assertEq(new Date(0).toISOString(), "1970-01-01T00:00:00.000Z");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn fake_timers_are_restored_after_each_test() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq, useFakeTimers, setSystemTime } from "str";
+            it("fakes timers", () => {
+                useFakeTimers();
+                setSystemTime(0);
+                assertEq(Date.now(), 0);
+            });
+            it("uses real timers", async () => {
+                assertEq(Date.now() > 0, true);
+                await new Promise((resolve) => setTimeout(resolve, 1));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> fakes timers ...
+            index.test.ts -> fakes timers PASSED
+            index.test.ts -> uses real timers ...
+            index.test.ts -> uses real timers PASSED
+            Ran 2 tests, 2 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_timeouts_work_with_fake_timers() -> Result<()> {
+    let context = Context::new()?;
+    context.write("str.config.json", r#"{ "timeout": 50 }"#)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, useFakeTimers } from "str";
+            it("hangs", async () => {
+                useFakeTimers();
+                await new Promise(() => {});
+            });
+        "#,
+    )?;
+    let stderr = context.run_assert_stderr("index.test.ts", 1);
+    assert_contains(stderr, "EXCEPTION: Error: test timed out after 50ms");
+    Ok(())
+}
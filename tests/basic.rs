@@ -2,9 +2,11 @@ mod common;
 
 use anyhow::Result;
 use common::assert_contains;
+use common::strip_ansi;
 use common::Context;
 use cradle::prelude::*;
 use pretty_assertions::assert_eq;
+use std::fs;
 
 #[test]
 fn simple_test_failure() -> Result<()> {
@@ -28,6 +30,14 @@ fn simple_test_failure() -> Result<()> {
             false
             src/index.test.ts -> fails FAILED
             Ran 1 test, 0 passed, 1 failed.
+
+            Failures:
+
+            src/index.test.ts -> fails
+                true
+                    !==
+                false
+                at src/index.test.ts:4:17
         ",
     )?;
     Ok(())
@@ -142,6 +152,14 @@ fn multiple_tests_last_failing() -> Result<()> {
             false
             src/index.test.ts -> fails FAILED
             Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            src/index.test.ts -> fails
+                true
+                    !==
+                false
+                at src/index.test.ts:9:17
         ",
     )?;
     Ok(())
@@ -174,6 +192,14 @@ fn multiple_tests_first_failing() -> Result<()> {
             src/index.test.ts -> works ...
             src/index.test.ts -> works PASSED
             Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            src/index.test.ts -> fails
+                true
+                    !==
+                false
+                at src/index.test.ts:4:17
         ",
     )?;
     Ok(())
@@ -209,6 +235,20 @@ fn multiple_failing_tests() -> Result<()> {
             false
             src/index.test.ts -> fails too FAILED
             Ran 2 tests, 0 passed, 2 failed.
+
+            Failures:
+
+            src/index.test.ts -> fails
+                true
+                    !==
+                false
+                at src/index.test.ts:4:17
+
+            src/index.test.ts -> fails too
+                true
+                    !==
+                false
+                at src/index.test.ts:7:17
         ",
     )?;
     Ok(())
@@ -246,6 +286,282 @@ fn jsx_and_tsx() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn tsconfig_jsx_factory() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "tsconfig.json",
+        r#"
+            {
+                // comments are allowed in tsconfig files
+                "compilerOptions": {
+                    "jsxFactory": "h",
+                },
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.tsx",
+        r#"
+            import { it } from "str";
+            const h = (tag: string) => tag.toUpperCase();
+            it("works", () => {
+                console.error(<div />);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.tsx",
+        0,
+        "
+            index.test.tsx -> works ...
+            DIV
+            index.test.tsx -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+fn write_jsx_runtime(context: &Context) -> Result<()> {
+    context.write(
+        "node_modules/my-jsx/package.json",
+        r#"{ "name": "my-jsx", "version": "1.0.0" }"#,
+    )?;
+    context.write(
+        "node_modules/my-jsx/jsx-runtime.js",
+        r#"
+            exports.jsx = (type, props) => ({ runtime: "jsx", type, props });
+            exports.jsxs = exports.jsx;
+        "#,
+    )?;
+    context.write(
+        "node_modules/my-jsx/jsx-dev-runtime.js",
+        r#"
+            exports.jsxDEV = (type, props) => ({ runtime: "dev", type, props });
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn tsconfig_automatic_jsx_runtime() -> Result<()> {
+    let context = Context::new()?;
+    write_jsx_runtime(&context)?;
+    context.write(
+        "tsconfig.json",
+        r#"
+            {
+                "compilerOptions": {
+                    "jsx": "react-jsx",
+                    "jsxImportSource": "my-jsx"
+                }
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.tsx",
+        r#"
+            import { it } from "str";
+            it("works", () => {
+                console.error(JSON.stringify(<div id="a">foo</div>));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.tsx",
+        0,
+        r#"
+            index.test.tsx -> works ...
+            {"runtime":"jsx","type":"div","props":{"id":"a","children":"foo"}}
+            index.test.tsx -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn tsconfig_automatic_jsx_runtime_in_development_mode() -> Result<()> {
+    let context = Context::new()?;
+    write_jsx_runtime(&context)?;
+    context.write(
+        "tsconfig.json",
+        r#"
+            {
+                "compilerOptions": {
+                    "jsx": "react-jsxdev",
+                    "jsxImportSource": "my-jsx"
+                }
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.tsx",
+        r#"
+            import { it } from "str";
+            it("works", () => {
+                const element = <div id="a" />;
+                console.error(element.runtime, element.type, element.props.id);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.tsx",
+        0,
+        "
+            index.test.tsx -> works ...
+            dev div a
+            index.test.tsx -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn tsconfig_paths() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "tsconfig.json",
+        r#"
+            {
+                "compilerOptions": {
+                    "baseUrl": ".",
+                    "paths": {
+                        "@app/*": ["src/*"]
+                    }
+                }
+            }
+        "#,
+    )?;
+    context.write(
+        "src/foo.ts",
+        r#"
+            export const foo = "foo from src";
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { foo } from "@app/foo";
+            it("works", () => {
+                console.error(foo);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            foo from src
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn tsconfig_base_url() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "tsconfig.json",
+        r#"
+            {
+                "compilerOptions": {
+                    "baseUrl": "src"
+                }
+            }
+        "#,
+    )?;
+    context.write(
+        "src/utils/index.ts",
+        r#"
+            export const utils = "utils from src";
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { utils } from "utils";
+            import { basename } from "path";
+            it("works", () => {
+                console.error(utils, basename("dir/file"));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            utils from src file
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn tsconfig_can_be_given_explicitly() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "tsconfig.base.json",
+        r#"
+            {
+                "compilerOptions": {
+                    "paths": {
+                        "~/*": ["./lib/*"]
+                    }
+                }
+            }
+        "#,
+    )?;
+    context.write(
+        "config/tsconfig.test.json",
+        r#"
+            {
+                "extends": "../tsconfig.base.json",
+                "compilerOptions": {
+                    "jsxFactory": "h"
+                }
+            }
+        "#,
+    )?;
+    context.write(
+        "lib/h.ts",
+        r#"
+            export const h = (tag: string) => `<${tag}> from lib`;
+        "#,
+    )?;
+    context.write(
+        "index.test.tsx",
+        r#"
+            import { it } from "str";
+            import { h } from "~/h";
+            it("works", () => {
+                console.error(<span />);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--tsconfig config/tsconfig.test.json index.test.tsx",
+        0,
+        "
+            index.test.tsx -> works ...
+            <span> from lib
+            index.test.tsx -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
 #[test]
 fn local_imports() -> Result<()> {
     let context = Context::new()?;
@@ -510,8 +826,10 @@ fn __dirname_works_in_subdirectories() -> Result<()> {
         r#"
             import { it } from "str";
             import { foo } from "./subdir/foo";
+            import { bar } from "./quote'd\\dir/bar";
             it("test", () => {
                 foo();
+                bar();
             });
         "#,
     )?;
@@ -520,16 +838,101 @@ fn __dirname_works_in_subdirectories() -> Result<()> {
         r#"
             export function foo() {
                 console.error(`__dirname: ${__dirname}`);
+                console.error(`__filename: ${__filename}`);
+                console.error(`import.meta.url: ${import.meta.url}`);
             }
         "#,
     )?;
+    context.write(
+        "quote'd\\dir/bar.js",
+        r#"
+            export function bar() {
+                console.error(`__dirname: ${__dirname}`);
+                console.error(`__filename: ${__filename}`);
+                console.error(`import.meta.url: ${import.meta.url}`);
+            }
+        "#,
+    )?;
+    let dir = context.temp_dir.path().to_string_lossy();
     context.run_assert(
         "index.test.ts",
         0,
         &format!(
             r#"
                 index.test.ts -> test ...
-                __dirname: {}/subdir
+                __dirname: {dir}/subdir
+                __filename: {dir}/subdir/foo.ts
+                import.meta.url: file://{dir}/subdir/foo.ts
+                __dirname: {dir}/quote'd\dir
+                __filename: {dir}/quote'd\dir/bar.js
+                import.meta.url: file://{dir}/quote%27d%5Cdir/bar.js
+                index.test.ts -> test PASSED
+                Ran 1 test, 1 passed, 0 failed.
+            "#,
+            dir = dir,
+        ),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn files_can_declare_their_own_dirname() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { dirname } from "path";
+            const __filename = "/declared/file.ts";
+            const { __dirname } = { __dirname: dirname(__filename) };
+            it("test", () => {
+                console.error(`__dirname: ${__dirname}`);
+                console.error(`__filename: ${__filename}`);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> test ...
+            __dirname: /declared
+            __filename: /declared/file.ts
+            index.test.ts -> test PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn files_can_import_filename() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "paths.ts",
+        r#"
+            export const __filename = "/imported/file.ts";
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { __filename } from "./paths";
+            it("test", () => {
+                console.error(`__filename: ${__filename}`);
+                console.error(`__dirname: ${__dirname}`);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        &format!(
+            r#"
+                index.test.ts -> test ...
+                __filename: /imported/file.ts
+                __dirname: {}
                 index.test.ts -> test PASSED
                 Ran 1 test, 1 passed, 0 failed.
             "#,
@@ -539,6 +942,18 @@ fn __dirname_works_in_subdirectories() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn injected_variables_do_not_shift_source_locations() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        "import { it } from \"str\"; it(\"fails\", () => { throw new Error(__dirname); });",
+    )?;
+    let stderr = strip_ansi(&context.run_assert_stderr("index.test.ts", 1))?;
+    assert_contains(stderr, "    at index.test.ts:1:53");
+    Ok(())
+}
+
 #[test]
 fn does_not_create_other_files_or_directories() -> Result<()> {
     let context = Context::new()?;
@@ -558,6 +973,91 @@ fn does_not_create_other_files_or_directories() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn does_not_leave_files_in_the_temp_directory() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { assertEq, it } from "str";
+            it("works", () => {
+                assertEq(true, true);
+            });
+        "#,
+    )?;
+    let tmp = context.temp_dir.path().join("tmp");
+    fs::create_dir(&tmp)?;
+    let Status(status) = context.run_command((
+        Env("TMPDIR", &tmp),
+        context.str_executable(),
+        "index.test.ts",
+    ));
+    assert!(status.success());
+    assert_eq!(fs::read_dir(&tmp)?.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn keep_bundle() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("works", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "--keep-bundle out index.test.ts",
+        0,
+        "
+            Bundle written to out/main.js
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    assert_contains(context.read("out/main.js")?, "\"works\"");
+    assert_contains(
+        context.read("out/main.js")?,
+        "//# sourceMappingURL=main.js.map",
+    );
+    assert_contains(context.read("out/main.js.map")?, "index.test.ts");
+    assert_contains(context.read("out/meta.json")?, "\"inputs\"");
+    assert_contains(context.read("out/meta.json")?, "index.test.ts");
+    Ok(())
+}
+
+#[test]
+fn print_runner() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("works", () => {});
+        "#,
+    )?;
+    assert_eq!(
+        context.run_stdout("--print-runner --timeout 100 index.test.ts"),
+        [
+            "",
+            "import { _strTestRunner, describe } from \"str\";",
+            "async function main() {",
+            "\t_strTestRunner.configure({\"timeout\":100});",
+            "\tawait _strTestRunner.enterTestFile(\"index.test.ts\", () => import(\"./index.test.ts\"));",
+            "\tawait _strTestRunner.runTests();",
+            "}",
+            "main();",
+            "",
+        ]
+        .join("\n")
+    );
+    let StdoutUntrimmed(files) = context.run_command("ls");
+    assert_eq!(files, "index.test.ts\nnode_modules\n");
+    Ok(())
+}
+
 #[test]
 fn node_apis() -> Result<()> {
     let context = Context::new()?;
@@ -578,3 +1078,176 @@ fn node_apis() -> Result<()> {
     )?;
     Ok(())
 }
+
+#[test]
+fn top_level_await_with_format_esm() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            const value = await Promise.resolve("from top-level await");
+            it("works", () => {
+                console.error(value);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--format esm index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            from top-level await
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn type_module_in_package_json_enables_esm() -> Result<()> {
+    let context = Context::new()?;
+    context.write("package.json", r#"{ "type": "module" }"#)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            const value = await Promise.resolve("from top-level await");
+            it("works", () => {
+                console.error(value);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            from top-level await
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn format_cjs_overrides_type_module() -> Result<()> {
+    let context = Context::new()?;
+    context.write("package.json", r#"{ "type": "module" }"#)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            const { basename } = require("path");
+            it("works", () => {
+                console.error(basename("dir/file"));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--format cjs index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            file
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn mts_and_cts_files() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "a.test.mts",
+        r#"
+            import { it } from "str";
+            import { foo } from "./foo.cts";
+            it("a", () => {
+                const x: string = foo;
+                console.error(x);
+            });
+        "#,
+    )?;
+    context.write(
+        "foo.cts",
+        r#"
+            export const foo: string = "foo from cts";
+        "#,
+    )?;
+    context.run_assert(
+        "--format esm a.test.mts",
+        0,
+        "
+            a.test.mts -> a ...
+            foo from cts
+            a.test.mts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn keep_bundle_with_format_esm() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("works", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "--format esm --keep-bundle out index.test.ts",
+        0,
+        "
+            Bundle written to out/main.mjs
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    assert_contains(
+        context.read("out/main.mjs")?,
+        "//# sourceMappingURL=main.mjs.map",
+    );
+    Ok(())
+}
+
+#[test]
+fn keep_bundle_outside_of_the_project_with_format_esm_is_rejected() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("works", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "--format esm --keep-bundle ../out index.test.ts",
+        1,
+        "
+            error: --keep-bundle: with the esm format, the bundle has to be inside the project, so that node can find node_modules: ../out
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn unsupported_formats_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    context.run_assert(
+        "--format iife index.test.ts",
+        1,
+        "
+            error: unsupported format: iife (supported formats: cjs, esm)
+        ",
+    )?;
+    Ok(())
+}
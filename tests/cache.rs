@@ -0,0 +1,241 @@
+mod common;
+
+use anyhow::Result;
+use common::Context;
+use std::fs;
+use std::path::PathBuf;
+
+fn cached_bundles(context: &Context) -> Result<Vec<PathBuf>> {
+    let mut result = Vec::new();
+    for entry in fs::read_dir(context.temp_dir.path().join("node_modules/.cache/str"))? {
+        result.push(entry?.path().join("main.js"));
+    }
+    Ok(result)
+}
+
+/// Modifies the cached bundle, so that we can tell whether it gets reused.
+fn mark_cached_bundle(context: &Context) -> Result<()> {
+    let bundles = cached_bundles(context)?;
+    assert_eq!(bundles.len(), 1);
+    let bundle = fs::read_to_string(&bundles[0])?;
+    fs::write(
+        &bundles[0],
+        format!("console.error(\"from cache\");\n{}", bundle),
+    )?;
+    Ok(())
+}
+
+fn write_project(context: &Context) -> Result<()> {
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { value } from "./dependency";
+            it("a", () => {
+                console.error(value);
+            });
+        "#,
+    )?;
+    context.write("dependency.ts", r#"export const value = "original";"#)?;
+    Ok(())
+}
+
+#[test]
+fn second_run_reuses_the_cached_bundle() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.run_assert_stderr("index.test.ts", 0);
+    mark_cached_bundle(&context)?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            from cache
+            index.test.ts -> a ...
+            original
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn editing_an_imported_file_invalidates_the_cache() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.run_assert_stderr("index.test.ts", 0);
+    mark_cached_bundle(&context)?;
+    context.write("dependency.ts", r#"export const value = "edited";"#)?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> a ...
+            edited
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    // The outdated bundle is left for runs that might still execute it.
+    assert_eq!(cached_bundles(&context)?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn no_cache_bypasses_the_cache() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.run_assert_stderr("index.test.ts", 0);
+    mark_cached_bundle(&context)?;
+    context.run_assert(
+        "--no-cache index.test.ts",
+        0,
+        "
+            index.test.ts -> a ...
+            original
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn cache_dir_can_be_configured() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "cacheDir": ".str-cache"
+            }
+        "#,
+    )?;
+    context.run_assert_stderr("index.test.ts", 0);
+    assert_eq!(
+        fs::read_dir(context.temp_dir.path().join(".str-cache"))?.count(),
+        1
+    );
+    assert!(!context
+        .temp_dir
+        .path()
+        .join("node_modules/.cache/str")
+        .exists());
+    Ok(())
+}
+
+#[test]
+fn files_using_dirname_can_be_cached() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { basename } from "path";
+            it("a", () => {
+                console.error(basename(__filename));
+            });
+        "#,
+    )?;
+    context.run_assert_stderr("index.test.ts", 0);
+    mark_cached_bundle(&context)?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            from cache
+            index.test.ts -> a ...
+            index.test.ts
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+fn write_old_cache_entries(context: &Context, count: usize) -> Result<()> {
+    for i in 0..count {
+        context.write(format!("node_modules/.cache/str/old-{}/main.js", i), "")?;
+    }
+    Ok(())
+}
+
+/// Returns the cache entries that were written by `str`.
+fn complete_cache_entries(context: &Context) -> Result<usize> {
+    Ok(cached_bundles(context)?
+        .iter()
+        .filter(|bundle| bundle.with_file_name("manifest.json").exists())
+        .count())
+}
+
+#[test]
+fn old_cache_entries_are_pruned() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    write_old_cache_entries(&context, 25)?;
+    context.run_assert_stderr("index.test.ts", 0);
+    assert_eq!(cached_bundles(&context)?.len(), 20);
+    assert_eq!(complete_cache_entries(&context)?, 1);
+    Ok(())
+}
+
+#[test]
+fn reused_cache_entries_are_kept_when_pruning() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.write(
+        "other.test.ts",
+        r#"
+            import { it } from "str";
+            it("b", () => {});
+        "#,
+    )?;
+    context.run_assert_stderr("index.test.ts", 0);
+    write_old_cache_entries(&context, 25)?;
+    context.run_assert_stderr("index.test.ts", 0);
+    context.run_assert_stderr("other.test.ts", 0);
+    assert_eq!(cached_bundles(&context)?.len(), 20);
+    assert_eq!(complete_cache_entries(&context)?, 2);
+    Ok(())
+}
+
+#[test]
+fn editing_an_extended_tsconfig_invalidates_the_cache() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { value } from "@dependency";
+            it("a", () => {
+                console.error(value);
+            });
+        "#,
+    )?;
+    context.write("original.ts", r#"export const value = "original";"#)?;
+    context.write("edited.ts", r#"export const value = "edited";"#)?;
+    context.write("tsconfig.json", r#"{ "extends": "./tsconfig.base.json" }"#)?;
+    context.write(
+        "tsconfig.base.json",
+        r#"{ "compilerOptions": { "paths": { "@dependency": ["./original.ts"] } } }"#,
+    )?;
+    context.run_assert_stderr("index.test.ts", 0);
+    mark_cached_bundle(&context)?;
+    context.write(
+        "tsconfig.base.json",
+        r#"{ "compilerOptions": { "paths": { "@dependency": ["./edited.ts"] } } }"#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> a ...
+            edited
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
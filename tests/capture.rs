@@ -0,0 +1,200 @@
+mod common;
+
+use anyhow::Result;
+use common::{assert_contains, strip_ansi, Context};
+
+#[test]
+fn output_of_passing_tests_is_hidden() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {
+                console.error("chatter");
+                console.warn("more chatter");
+                process.stderr.write("raw chatter\n");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--capture index.test.ts",
+        0,
+        "
+            index.test.ts -> a ...
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn output_of_failing_tests_is_replayed() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            it("a", () => {
+                console.error("passing chatter");
+            });
+            it("b", () => {
+                console.log("log output");
+                process.stderr.write("partial ");
+                process.stderr.write("line\n");
+                console.error("multi\nline");
+                assertEq(1, 2);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--capture index.test.ts",
+        1,
+        "
+            index.test.ts -> a ...
+            index.test.ts -> a PASSED
+            index.test.ts -> b ...
+                [console.log] log output
+                [stderr] partial line
+                [console.error] multi
+                [console.error] line
+            1
+                !==
+            2
+            index.test.ts -> b FAILED
+            Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> b
+                1
+                    !==
+                2
+                at index.test.ts:11:17
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn output_of_hooks_is_captured() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, beforeEach, afterEach } from "str";
+            beforeEach(() => {
+                console.error("beforeEach");
+            });
+            afterEach(() => {
+                console.error("afterEach");
+            });
+            it("passes", () => {});
+            it("fails", () => {
+                throw "foo";
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--capture index.test.ts",
+        1,
+        "
+            index.test.ts -> passes ...
+            index.test.ts -> passes PASSED
+            index.test.ts -> fails ...
+                [console.error] beforeEach
+                [console.error] afterEach
+            EXCEPTION: foo
+            index.test.ts -> fails FAILED
+            Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> fails
+                EXCEPTION: foo
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn output_of_before_all_and_after_all_is_captured() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, beforeAll, afterAll } from "str";
+            beforeAll(() => {
+                console.error("beforeAll");
+            });
+            afterAll(() => {
+                console.error("afterAll");
+            });
+            it("a", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "--capture index.test.ts",
+        0,
+        "
+            index.test.ts -> a ...
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn output_of_failing_before_all_is_replayed() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, beforeAll } from "str";
+            beforeAll(() => {
+                console.error("connecting");
+                throw new Error("connection refused");
+            });
+            it("a", () => {});
+        "#,
+    )?;
+    let stderr = strip_ansi(&context.run_assert_stderr("--capture index.test.ts", 1))?;
+    assert_contains(&stderr, "    [console.error] connecting\n");
+    assert_contains(&stderr, "Error: connection refused");
+    Ok(())
+}
+
+#[test]
+fn no_capture_overrides_config() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "capture": true
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {
+                console.error("chatter");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--no-capture index.test.ts",
+        0,
+        "
+            index.test.ts -> a ...
+            chatter
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
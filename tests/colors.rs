@@ -28,6 +28,11 @@ fn colorizes_output() -> Result<()> {
             "index.test.ts -> b ...".yellow(),
             "index.test.ts -> b PASSED".green(),
             format!("Ran 2 tests, {}, {}.", "1 passed".green(), "1 failed".red()).normal(),
+            "".normal(),
+            "Failures:".normal(),
+            "".normal(),
+            "index.test.ts -> a".red(),
+            "    EXCEPTION: foo".normal(),
         ]
         .into_iter()
         .map(|string| string.to_string())
@@ -94,6 +99,14 @@ fn all_fail() -> Result<()> {
             "EXCEPTION: foo".normal(),
             "index.test.ts -> b FAILED".red(),
             format!("Ran 2 tests, {}, {}.", "0 passed".green(), "2 failed".red()).normal(),
+            "".normal(),
+            "Failures:".normal(),
+            "".normal(),
+            "index.test.ts -> a".red(),
+            "    EXCEPTION: foo".normal(),
+            "".normal(),
+            "index.test.ts -> b".red(),
+            "    EXCEPTION: foo".normal(),
         ]
         .into_iter()
         .map(|string| string.to_string())
@@ -188,3 +201,148 @@ fn ignored_tests() -> Result<()> {
     )?;
     Ok(())
 }
+
+#[test]
+fn todo_tests() -> Result<()> {
+    colored::control::set_override(true);
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {});
+            it.todo("b");
+        "#,
+    )?;
+    context.run_assert_with_colors(
+        "index.test.ts",
+        0,
+        &vec![
+            "index.test.ts -> a ...".yellow(),
+            "index.test.ts -> a PASSED".green(),
+            "index.test.ts -> b TODO".cyan(),
+            format!(
+                "Ran 1 test, {}, 0 failed, {}.",
+                "1 passed".green(),
+                "1 todo".cyan()
+            )
+            .normal(),
+        ]
+        .into_iter()
+        .map(|string| string.to_string())
+        .collect::<Vec<_>>()
+        .join("\n"),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn skip_if_and_run_if() -> Result<()> {
+    colored::control::set_override(true);
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it.skipIf(true)("a", () => {});
+            it.skipIf(false)("b", () => {});
+            it.runIf(false)("c", () => {});
+            it.runIf(true)("d", () => {});
+        "#,
+    )?;
+    context.run_assert_with_colors(
+        "index.test.ts",
+        0,
+        &vec![
+            "index.test.ts -> a SKIPPED".yellow(),
+            "index.test.ts -> b ...".yellow(),
+            "index.test.ts -> b PASSED".green(),
+            "index.test.ts -> c SKIPPED".yellow(),
+            "index.test.ts -> d ...".yellow(),
+            "index.test.ts -> d PASSED".green(),
+            format!(
+                "Ran 2 tests, {}, 0 failed, {}.",
+                "2 passed".green(),
+                "2 skipped".yellow()
+            )
+            .normal(),
+        ]
+        .into_iter()
+        .map(|string| string.to_string())
+        .collect::<Vec<_>>()
+        .join("\n"),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn skipping_at_runtime() -> Result<()> {
+    colored::control::set_override(true);
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", (ctx) => {
+                ctx.skip("docker is not installed");
+            });
+        "#,
+    )?;
+    context.run_assert_with_colors(
+        "index.test.ts",
+        0,
+        &vec![
+            "index.test.ts -> a ...".yellow(),
+            "index.test.ts -> a SKIPPED (docker is not installed)".yellow(),
+            format!(
+                "Ran 0 tests, {}, 0 failed, {}.",
+                "0 passed".green(),
+                "1 skipped".yellow()
+            )
+            .normal(),
+        ]
+        .into_iter()
+        .map(|string| string.to_string())
+        .collect::<Vec<_>>()
+        .join("\n"),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn ignored_skipped_and_todo_are_counted_separately() -> Result<()> {
+    colored::control::set_override(true);
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, xit } from "str";
+            xit("a", () => {});
+            it.skipIf(true)("b", () => {});
+            it.todo("c");
+            it.todo("d");
+        "#,
+    )?;
+    context.run_assert_with_colors(
+        "index.test.ts",
+        0,
+        &vec![
+            "index.test.ts -> a IGNORED".yellow(),
+            "index.test.ts -> b SKIPPED".yellow(),
+            "index.test.ts -> c TODO".cyan(),
+            "index.test.ts -> d TODO".cyan(),
+            format!(
+                "Ran 0 tests, {}, 0 failed, 1 ignored, {}, {}.",
+                "0 passed".green(),
+                "1 skipped".yellow(),
+                "2 todo".cyan()
+            )
+            .normal(),
+        ]
+        .into_iter()
+        .map(|string| string.to_string())
+        .collect::<Vec<_>>()
+        .join("\n"),
+    )?;
+    Ok(())
+}
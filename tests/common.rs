@@ -26,6 +26,7 @@ pub fn assert_contains<A: AsRef<str>, B: AsRef<str>>(a: A, b: B) {
 pub struct Context {
     pub temp_dir: TempDir,
     repo_dir: PathBuf,
+    working_dir: PathBuf,
 }
 
 impl Context {
@@ -43,7 +44,44 @@ impl Context {
                     .join(dependency.file_name().unwrap()),
             )?;
         }
-        Ok(Context { temp_dir, repo_dir })
+        Ok(Context {
+            temp_dir,
+            repo_dir,
+            working_dir: PathBuf::new(),
+        })
+    }
+
+    /// Makes `str` run in the given directory, relative to the temp dir.
+    pub fn cd<P: AsRef<Path>>(&mut self, dir: P) {
+        self.working_dir = dir.as_ref().to_owned();
+    }
+
+    /// Lays out a yarn workspace with the given packages, given as
+    /// `(name, dir)` pairs. Every package gets a `package.json` with
+    /// `src/index.ts` as its main file and is symlinked into the root
+    /// `node_modules`, like yarn does.
+    pub fn workspace(&self, packages: &[(&str, &str)]) -> Result<()> {
+        let dirs = packages
+            .iter()
+            .map(|(_, dir)| format!("{:?}", dir))
+            .collect::<Vec<_>>();
+        self.write(
+            "package.json",
+            &format!(
+                r#"{{ "private": true, "workspaces": [{}] }}"#,
+                dirs.join(", ")
+            ),
+        )?;
+        for (name, dir) in packages {
+            self.write(
+                Path::new(dir).join("package.json"),
+                &format!(r#"{{ "name": {:?}, "main": "src/index.ts" }}"#, name),
+            )?;
+            let link = self.temp_dir.path().join("node_modules").join(name);
+            create_dir_all(link.parent().ok_or(anyhow!("no parent"))?)?;
+            unix::fs::symlink(self.temp_dir.path().join(dir), link)?;
+        }
+        Ok(())
     }
 
     pub fn write<P: AsRef<Path>>(&self, path: P, content: &str) -> Result<()> {
@@ -59,17 +97,30 @@ impl Context {
         Ok(fs::read_to_string(file)?)
     }
 
+    pub fn str_executable(&self) -> PathBuf {
+        self.repo_dir.join("str")
+    }
+
     pub fn run(&self, args: &str) -> Output {
         let (Stderr(stderr), Status(status)) = self.run_command((
-            self.repo_dir.join("str"),
+            self.str_executable(),
             args.split_whitespace().collect::<Vec<&str>>(),
         ));
         eprintln!("STDERR:\n{}STDERR END", stderr);
         Output { status, stderr }
     }
 
+    pub fn run_stdout(&self, args: &str) -> String {
+        let StdoutUntrimmed(stdout) = self.run_command((
+            self.str_executable(),
+            args.split_whitespace().collect::<Vec<&str>>(),
+        ));
+        stdout
+    }
+
     pub fn run_command<I: Input, O: cradle::Output>(&self, i: I) -> O {
-        let (StdoutUntrimmed(stdout), o) = (CurrentDir(self.temp_dir.path()), i).run_output();
+        let (StdoutUntrimmed(stdout), o) =
+            (CurrentDir(self.temp_dir.path().join(&self.working_dir)), i).run_output();
         print!("{}", stdout);
         o
     }
@@ -82,7 +133,26 @@ impl Context {
     ) -> Result<()> {
         let stderr = self.run_assert_stderr(args, expected_exit_code);
         assert_eq!(
-            strip_ansi(&stderr)?.lines().collect::<Vec<_>>(),
+            replace_durations(&strip_ansi(&stderr)?, "")
+                .lines()
+                .collect::<Vec<_>>(),
+            expected_stderr.unindent().lines().collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    /// Like `run_assert`, but keeps durations in the output, masked as `(Xms)`.
+    pub fn run_assert_with_durations(
+        &self,
+        args: &str,
+        expected_exit_code: i32,
+        expected_stderr: &str,
+    ) -> Result<()> {
+        let stderr = self.run_assert_stderr(args, expected_exit_code);
+        assert_eq!(
+            replace_durations(&strip_ansi(&stderr)?, "(Xms)")
+                .lines()
+                .collect::<Vec<_>>(),
             expected_stderr.unindent().lines().collect::<Vec<_>>()
         );
         Ok(())
@@ -96,7 +166,7 @@ impl Context {
     ) -> Result<()> {
         let stderr = self.run_assert_stderr(args, expected_exit_code);
         assert_eq!(
-            stderr.lines().collect::<Vec<_>>(),
+            replace_durations(&stderr, "").lines().collect::<Vec<_>>(),
             expected_stderr.unindent().lines().collect::<Vec<_>>()
         );
         Ok(())
@@ -117,3 +187,29 @@ pub struct Output {
 pub fn strip_ansi(input: &str) -> Result<String> {
     Ok(String::from_utf8(strip_ansi_escapes::strip(input)?)?)
 }
+
+/// Replaces durations like `(123ms)` with `replacement`. An empty
+/// replacement also removes the space in front of the duration.
+pub fn replace_durations(input: &str, replacement: &str) -> String {
+    let mut result = String::new();
+    let mut rest = input;
+    while let Some(start) = rest.find('(') {
+        let after = &rest[start + 1..];
+        let digits = after.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits > 0 && after[digits..].starts_with("ms)") {
+            let prefix = &rest[..start];
+            if replacement.is_empty() {
+                result.push_str(prefix.strip_suffix(' ').unwrap_or(prefix));
+            } else {
+                result.push_str(prefix);
+                result.push_str(replacement);
+            }
+            rest = &after[digits + "ms)".len()..];
+        } else {
+            result.push_str(&rest[..=start]);
+            rest = after;
+        }
+    }
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,544 @@
+mod common;
+
+use anyhow::Result;
+use common::Context;
+
+#[test]
+fn config_json_specifies_test_files() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "testFiles": ["src/**/*.test.ts"]
+            }
+        "#,
+    )?;
+    context.write(
+        "src/a.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {});
+        "#,
+    )?;
+    context.write(
+        "src/nested/b.test.ts",
+        r#"
+            import { it } from "str";
+            it("b", () => {});
+        "#,
+    )?;
+    context.write(
+        "src/helper.ts",
+        r#"
+            throw "should not be imported";
+        "#,
+    )?;
+    context.run_assert(
+        "",
+        0,
+        "
+            src/a.test.ts -> a ...
+            src/a.test.ts -> a PASSED
+            src/nested/b.test.ts -> b ...
+            src/nested/b.test.ts -> b PASSED
+            Ran 2 tests, 2 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn ignore_excludes_test_files() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "testFiles": ["**/*.test.ts"],
+                "ignore": ["fixtures/**"]
+            }
+        "#,
+    )?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {});
+        "#,
+    )?;
+    context.write(
+        "fixtures/b.test.ts",
+        r#"
+            import { it } from "str";
+            it("b", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "",
+        0,
+        "
+            a.test.ts -> a ...
+            a.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn config_in_package_json() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "package.json",
+        r#"
+            {
+                "name": "test-project",
+                "str": {
+                    "testFiles": ["*.test.ts"]
+                }
+            }
+        "#,
+    )?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "",
+        0,
+        "
+            a.test.ts -> a ...
+            a.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn package_json_without_str_field_is_ignored() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "package.json",
+        r#"
+            {
+                "name": "test-project"
+            }
+        "#,
+    )?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "a.test.ts",
+        0,
+        "
+            a.test.ts -> a ...
+            a.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn config_in_typescript() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.ts",
+        r#"
+            const pattern: string = "*.test.ts";
+            export default {
+                testFiles: [pattern],
+            };
+        "#,
+    )?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "",
+        0,
+        "
+            a.test.ts -> a ...
+            a.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn typescript_configs_can_import_packages() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "node_modules/shared-config/package.json",
+        r#"{ "name": "shared-config", "main": "index.js" }"#,
+    )?;
+    context.write(
+        "node_modules/shared-config/index.js",
+        r#"exports.testFiles = ["*.test.ts"];"#,
+    )?;
+    context.write(
+        "str.config.ts",
+        r#"
+            import { testFiles } from "shared-config";
+            export default { testFiles };
+        "#,
+    )?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "",
+        0,
+        "
+            a.test.ts -> a ...
+            a.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn config_file_can_be_given_explicitly() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "config/str.json",
+        r#"
+            {
+                "testFiles": ["*.test.ts"]
+            }
+        "#,
+    )?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "--config config/str.json",
+        0,
+        "
+            a.test.ts -> a ...
+            a.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_files_on_the_command_line_override_config() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "testFiles": ["*.test.ts"]
+            }
+        "#,
+    )?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {});
+        "#,
+    )?;
+    context.write(
+        "b.test.ts",
+        r#"
+            import { it } from "str";
+            it("b", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "b.test.ts",
+        0,
+        "
+            b.test.ts -> b ...
+            b.test.ts -> b PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn unknown_keys_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "testFiles": ["*.test.ts"],
+                "unknownKey": true
+            }
+        "#,
+    )?;
+    context.run_assert(
+        "",
+        1,
+        r#"
+            error: str.config.json: unknown field "unknownKey"
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn unknown_flags_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    context.run_assert(
+        "--unknown-flag",
+        1,
+        "
+            error: unknown flag: --unknown-flag
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn timeout() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "timeout": 50
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("fast", () => {});
+            it("slow", async () => {
+                await new Promise((resolve) => setTimeout(resolve, 10000));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        1,
+        "
+            index.test.ts -> fast ...
+            index.test.ts -> fast PASSED
+            index.test.ts -> slow ...
+            EXCEPTION: Error: test timed out after 50ms
+            index.test.ts -> slow FAILED
+            Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> slow
+                EXCEPTION: Error: test timed out after 50ms
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn command_line_flags_override_config() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "timeout": 10000
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("slow", async () => {
+                await new Promise((resolve) => setTimeout(resolve, 10000));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts --timeout 50",
+        1,
+        "
+            index.test.ts -> slow ...
+            EXCEPTION: Error: test timed out after 50ms
+            index.test.ts -> slow FAILED
+            Ran 1 test, 0 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> slow
+                EXCEPTION: Error: test timed out after 50ms
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn env() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "env": { "FOO": "from config", "BAR": "from config" }
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("env", () => {
+                console.error(process.env.FOO, process.env.BAR);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts --env BAR=from-flag",
+        0,
+        "
+            index.test.ts -> env ...
+            from config from-flag
+            index.test.ts -> env PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn env_file() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        ".env.test",
+        r#"
+            # comments are ignored
+            FOO=from env file
+            export BAR="quoted value"
+            HOME=/from/env/file
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("env", () => {
+                console.error(process.env.FOO);
+                console.error(process.env.BAR);
+                console.error(process.env.HOME);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--env-file .env.test index.test.ts",
+        0,
+        "
+            index.test.ts -> env ...
+            from env file
+            quoted value
+            /from/env/file
+            index.test.ts -> env PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn env_overrides_env_files() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "envFiles": [".env.test"],
+                "env": { "FOO": "from config" }
+            }
+        "#,
+    )?;
+    context.write(".env.test", "FOO=from env file\nBAR=from env file\n")?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("env", () => {
+                console.error(process.env.FOO, process.env.BAR);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> env ...
+            from config from env file
+            index.test.ts -> env PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn define() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "esbuild": {
+                    "define": { "FEATURE_NAME": "\"from config\"" }
+                }
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            declare const FEATURE_FLAG: boolean;
+            declare const FEATURE_NAME: string;
+            it("define", () => {
+                console.error(FEATURE_FLAG, FEATURE_NAME);
+                console.error(process.env.NODE_ENV);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--define FEATURE_FLAG=true --define process.env.NODE_ENV=\"test\" index.test.ts",
+        0,
+        "
+            index.test.ts -> define ...
+            true from config
+            test
+            index.test.ts -> define PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
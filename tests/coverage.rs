@@ -0,0 +1,183 @@
+mod common;
+
+use anyhow::Result;
+use common::{replace_durations, strip_ansi, Context};
+use pretty_assertions::assert_eq;
+use unindent::Unindent;
+
+fn write_project(context: &Context) -> Result<()> {
+    context.write(
+        "math.ts",
+        r#"
+            export function add(a: number, b: number): number {
+              return a + b;
+            }
+
+            export function unused(): number {
+              return 42;
+            }
+        "#
+        .unindent()
+        .as_str(),
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            import { add } from "./math";
+            it("adds", () => {
+                assertEq(add(1, 2), 3);
+            });
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn prints_a_coverage_summary() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.run_assert(
+        "--coverage index.test.ts",
+        0,
+        "
+            index.test.ts -> adds ...
+            index.test.ts -> adds PASSED
+            Ran 1 test, 1 passed, 0 failed.
+
+            Coverage:
+            File      | Lines        | Uncovered lines
+            math.ts   | 50.00% (3/6) | 5-7
+            All files | 50.00% (3/6) |
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn collects_coverage_from_parallel_workers() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.write(
+        "other.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            import { add } from "./math";
+            it("adds too", () => {
+                assertEq(add(2, 2), 4);
+            });
+        "#,
+    )?;
+    let stderr =
+        context.run_assert_stderr("--coverage --parallelism 2 index.test.ts other.test.ts", 0);
+    assert!(replace_durations(&strip_ansi(&stderr)?, "").ends_with(
+        &"
+            Ran 2 tests, 2 passed, 0 failed.
+
+            Coverage:
+            File      | Lines        | Uncovered lines
+            math.ts   | 50.00% (3/6) | 5-7
+            All files | 50.00% (3/6) |
+        "
+        .unindent()
+    ));
+    Ok(())
+}
+
+#[test]
+fn writes_lcov_info() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.run_assert_stderr("--coverage index.test.ts", 0);
+    assert_eq!(
+        context.read("coverage/lcov.info")?,
+        "
+            TN:
+            SF:math.ts
+            DA:1,1
+            DA:2,1
+            DA:3,1
+            DA:5,0
+            DA:6,0
+            DA:7,0
+            LF:6
+            LH:3
+            end_of_record
+        "
+        .unindent()
+    );
+    Ok(())
+}
+
+#[test]
+fn writes_a_json_summary() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.run_assert_stderr("--coverage index.test.ts", 0);
+    assert_eq!(
+        context.read("coverage/coverage-summary.json")?,
+        r#"
+            {
+              "math.ts": {
+                "lines": {
+                  "total": 6,
+                  "covered": 3,
+                  "pct": 50
+                }
+              },
+              "total": {
+                "lines": {
+                  "total": 6,
+                  "covered": 3,
+                  "pct": 50
+                }
+              }
+            }
+        "#
+        .unindent()
+    );
+    Ok(())
+}
+
+#[test]
+fn fails_when_coverage_is_below_the_threshold() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.run_assert(
+        "--coverage-threshold 80 index.test.ts",
+        1,
+        "
+            index.test.ts -> adds ...
+            index.test.ts -> adds PASSED
+            Ran 1 test, 1 passed, 0 failed.
+
+            Coverage:
+            File      | Lines        | Uncovered lines
+            math.ts   | 50.00% (3/6) | 5-7
+            All files | 50.00% (3/6) |
+            Line coverage of 50.00% is below the threshold of 80%.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn virtual_modules_are_not_reported() -> Result<()> {
+    let context = Context::new()?;
+    write_project(&context)?;
+    context.write("dir.ts", "export const dir = __dirname;\n")?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            import { dir } from "./dir";
+            it("works", () => {
+                assertEq(typeof dir, "string");
+            });
+        "#,
+    )?;
+    let stderr = context.run_assert_stderr("--coverage index.test.ts", 0);
+    assert!(stderr.contains("dir.ts"));
+    assert!(!stderr.contains("str-file-info"));
+    Ok(())
+}
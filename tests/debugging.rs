@@ -0,0 +1,85 @@
+mod common;
+
+use anyhow::Result;
+use common::{assert_contains, strip_ansi, Context};
+
+#[test]
+fn filter_runs_only_matching_tests() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, describe, beforeAll } from "str";
+            describe("math", () => {
+                beforeAll(() => console.error("beforeAll math"));
+                it("adds", () => {});
+                it("subtracts", () => {});
+            });
+            describe("strings", () => {
+                beforeAll(() => console.error("beforeAll strings"));
+                it("concatenates", () => {});
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--filter subtracts index.test.ts",
+        0,
+        "
+            beforeAll math
+            index.test.ts -> math -> subtracts ...
+            index.test.ts -> math -> subtracts PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn filter_matches_describe_blocks() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it, describe } from "str";
+            describe("math", () => {
+                it("adds", () => {});
+            });
+            it("other", () => {});
+        "#,
+    )?;
+    context.write(
+        "b.test.ts",
+        r#"
+            import { it } from "str";
+            it("b", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "--filter=math a.test.ts b.test.ts",
+        0,
+        "
+            a.test.ts -> math -> adds ...
+            a.test.ts -> math -> adds PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn inspect_starts_the_node_inspector() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {});
+            it("b", () => {});
+        "#,
+    )?;
+    let stderr = strip_ansi(&context.run_assert_stderr("--inspect=0 --filter b index.test.ts", 0))?;
+    assert_contains(&stderr, "Debugger listening on ws://127.0.0.1:");
+    assert_contains(&stderr, "index.test.ts -> b PASSED");
+    assert!(!stderr.contains("index.test.ts -> a"));
+    Ok(())
+}
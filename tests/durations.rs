@@ -0,0 +1,127 @@
+mod common;
+
+use anyhow::Result;
+use common::{assert_contains, Context};
+
+#[test]
+fn durations_are_shown_for_tests_and_the_whole_run() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("passes", () => {});
+            it("fails", () => {
+                throw "foo";
+            });
+        "#,
+    )?;
+    context.run_assert_with_durations(
+        "index.test.ts",
+        1,
+        "
+            index.test.ts -> passes ...
+            index.test.ts -> passes PASSED (Xms)
+            index.test.ts -> fails ...
+            EXCEPTION: foo
+            index.test.ts -> fails FAILED (Xms)
+            Ran 2 tests, 1 passed, 1 failed. (Xms)
+
+            Failures:
+
+            index.test.ts -> fails
+                EXCEPTION: foo
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn slow_tests_are_listed() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            const sleep = (ms: number) =>
+                new Promise((resolve) => setTimeout(resolve, ms));
+            it("fast", () => {});
+            it("slow", async () => {
+                await sleep(300);
+            });
+            it("slower", async () => {
+                await sleep(500);
+            });
+        "#,
+    )?;
+    context.run_assert_with_durations(
+        "--slow 200 index.test.ts",
+        0,
+        "
+            index.test.ts -> fast ...
+            index.test.ts -> fast PASSED (Xms)
+            index.test.ts -> slow ...
+            index.test.ts -> slow PASSED (Xms)
+            index.test.ts -> slower ...
+            index.test.ts -> slower PASSED (Xms)
+            Ran 3 tests, 3 passed, 0 failed. (Xms)
+
+            Slowest tests:
+                (Xms) index.test.ts -> slower
+                (Xms) index.test.ts -> slow
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn slow_durations_are_highlighted() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("slow", async () => {
+                await new Promise((resolve) => setTimeout(resolve, 100));
+            });
+        "#,
+    )?;
+    let stderr = context.run_assert_stderr("--slow 50 index.test.ts", 0);
+    assert_contains(stderr, "index.test.ts -> slow PASSED\u{1b}[0m \u{1b}[33m(");
+    Ok(())
+}
+
+#[test]
+fn slow_threshold_can_be_configured() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "slow": 50
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("slow", async () => {
+                await new Promise((resolve) => setTimeout(resolve, 100));
+            });
+        "#,
+    )?;
+    context.run_assert_with_durations(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> slow ...
+            index.test.ts -> slow PASSED (Xms)
+            Ran 1 test, 1 passed, 0 failed. (Xms)
+
+            Slowest tests:
+                (Xms) index.test.ts -> slow
+        ",
+    )?;
+    Ok(())
+}
@@ -0,0 +1,189 @@
+mod common;
+
+use anyhow::Result;
+use common::Context;
+
+#[test]
+fn it_each_with_tuples() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            it.each([
+                [1, 2, 3],
+                [2, 3, 5],
+            ])("adds %d and %d", (a, b, sum) => {
+                assertEq(a + b, sum);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> adds 1 and 2 ...
+            index.test.ts -> adds 1 and 2 PASSED
+            index.test.ts -> adds 2 and 3 ...
+            index.test.ts -> adds 2 and 3 PASSED
+            Ran 2 tests, 2 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn it_each_with_objects() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            it.each([
+                { input: "foo", expected: { length: 3 } },
+                { input: "hello", expected: { length: 4 } },
+            ])("$input has length $expected.length", ({ input, expected }) => {
+                assertEq(input.length, expected.length);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        1,
+        r#"
+            index.test.ts -> foo has length 3 ...
+            index.test.ts -> foo has length 3 PASSED
+            index.test.ts -> hello has length 4 ...
+            5
+                !==
+            4
+            index.test.ts -> hello has length 4 FAILED
+            Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> hello has length 4
+                5
+                    !==
+                4
+                at index.test.ts:7:17
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn it_each_placeholders() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it.each([
+                ["a", { b: 1 }],
+                ["c", [2]],
+            ])("%# %s %j 100%%", () => {});
+            it.each([true, null])("single %p", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> 0 a {"b":1} 100% ...
+            index.test.ts -> 0 a {"b":1} 100% PASSED
+            index.test.ts -> 1 c [2] 100% ...
+            index.test.ts -> 1 c [2] 100% PASSED
+            index.test.ts -> single true ...
+            index.test.ts -> single true PASSED
+            index.test.ts -> single null ...
+            index.test.ts -> single null PASSED
+            Ran 4 tests, 4 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn describe_each() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { describe, it, assertEq } from "str";
+            describe.each([
+                ["numbers", 1, 2],
+                ["strings", "a", "b"],
+            ])("%s", (_, a, b) => {
+                it("are different", () => {
+                    assertEq(a === b, false);
+                });
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> numbers -> are different ...
+            index.test.ts -> numbers -> are different PASSED
+            index.test.ts -> strings -> are different ...
+            index.test.ts -> strings -> are different PASSED
+            Ran 2 tests, 2 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn filter_matches_single_rows() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it.each(["a", "b", "c"])("row %s", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "--filter b index.test.ts",
+        0,
+        r#"
+            index.test.ts -> row b ...
+            index.test.ts -> row b PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn it_each_passes_the_test_context() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it.each([{ n: 1 }])("object $n", (row, ctx) => {
+                console.error(row.n, ctx.fullName);
+            });
+            it.each([[2, "two"]] as const)("tuple %d", (n, name, ctx) => {
+                console.error(n, name, ctx.fullName);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> object 1 ...
+            1 index.test.ts -> object 1
+            index.test.ts -> object 1 PASSED
+            index.test.ts -> tuple 2 ...
+            2 two index.test.ts -> tuple 2
+            index.test.ts -> tuple 2 PASSED
+            Ran 2 tests, 2 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
@@ -26,6 +26,11 @@ fn it_catches_exceptions() -> Result<()> {
             index.test.ts -> b ...
             index.test.ts -> b PASSED
             Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> a
+                EXCEPTION: foo
         ",
     )?;
     Ok(())
@@ -54,6 +59,12 @@ fn it_catches_undefined_identifiers() -> Result<()> {
             index.test.ts -> b ...
             index.test.ts -> b PASSED
             Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> a
+                EXCEPTION: ReferenceError: notDefined is not defined
+                at index.test.ts:4:17
         ",
     )?;
     Ok(())
@@ -84,6 +95,11 @@ fn it_catches_async_exceptions() -> Result<()> {
             index.test.ts -> b ...
             index.test.ts -> b PASSED
             Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> a
+                EXCEPTION: foo
         ",
     )?;
     Ok(())
@@ -117,6 +133,12 @@ fn after_each_gets_run_when_it_throws() -> Result<()> {
             afterEach
             index.test.ts -> b PASSED
             Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> a
+                EXCEPTION: ReferenceError: notDefined is not defined
+                at index.test.ts:7:17
         ",
     )?;
     Ok(())
@@ -143,6 +165,11 @@ fn prints_exceptions_in_before_eachs() -> Result<()> {
             EXCEPTION: test error
             index.test.ts -> a FAILED
             Ran 1 test, 0 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> a
+                EXCEPTION: test error
         ",
     )?;
     Ok(())
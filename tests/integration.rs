@@ -1,7 +1,7 @@
 mod common;
 
 use anyhow::Result;
-use common::strip_ansi;
+use common::{replace_durations, strip_ansi};
 use cradle::prelude::*;
 use pretty_assertions::assert_eq;
 use unindent::Unindent;
@@ -36,7 +36,7 @@ fn integration_test() -> Result<()> {
     let (Status(status), Stderr(output)) = run_command(&image, "failing.test.ts").run_result()?;
     assert_eq!(status.code(), Some(1));
     assert_eq!(
-        strip_ansi(&output)?,
+        replace_durations(&strip_ansi(&output)?, ""),
         "
             failing.test.ts -> fails ...
             true
@@ -44,13 +44,21 @@ fn integration_test() -> Result<()> {
             false
             failing.test.ts -> fails FAILED
             Ran 1 test, 0 passed, 1 failed.
+
+            Failures:
+
+            failing.test.ts -> fails
+                true
+                    !==
+                false
+                at failing.test.ts:4:3
         "
         .unindent()
     );
     let (Status(status), Stderr(output)) = run_command(&image, "passing.test.ts").run_result()?;
     assert!(status.success());
     assert_eq!(
-        strip_ansi(&output)?,
+        replace_durations(&strip_ansi(&output)?, ""),
         "
             passing.test.ts -> passes ...
             passing.test.ts -> passes PASSED
@@ -0,0 +1,208 @@
+mod common;
+
+use anyhow::Result;
+use common::{assert_contains, strip_ansi, Context};
+
+#[test]
+fn json_files_can_be_imported() -> Result<()> {
+    let context = Context::new()?;
+    context.write("data.json", r#"{ "foo": [1, 2, 3] }"#)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            import data from "./data.json";
+            it("works", () => {
+                assertEq(data, { foo: [1, 2, 3] });
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn txt_files_are_imported_as_text() -> Result<()> {
+    let context = Context::new()?;
+    context.write("data.txt", "some text\n")?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            import text from "./data.txt";
+            it("works", () => {
+                assertEq(text, "some text\n");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn loaders_can_be_configured_per_extension() -> Result<()> {
+    let context = Context::new()?;
+    context.write("query.graphql", "query { foo }\n")?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "esbuild": { "loader": { ".graphql": "text" } }
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            import query from "./query.graphql";
+            it("works", () => {
+                assertEq(query, "query { foo }\n");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn base64_loader() -> Result<()> {
+    let context = Context::new()?;
+    context.write("data.bin", "foo")?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            import data from "./data.bin";
+            it("works", () => {
+                assertEq(data, "Zm9v");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--loader .bin=base64 index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn binary_loader() -> Result<()> {
+    let context = Context::new()?;
+    context.write("data.bin", "foo")?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            import data from "./data.bin";
+            it("works", () => {
+                assertEq(data instanceof Uint8Array, true);
+                assertEq(Array.from(data), [102, 111, 111]);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--loader .bin=binary index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn file_loader_imports_the_absolute_path() -> Result<()> {
+    let context = Context::new()?;
+    context.write("fixtures/image.png", "not really a png")?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { readFileSync } from "fs";
+            import image from "./fixtures/image.png";
+            it("works", () => {
+                console.error(image);
+                console.error(readFileSync(image, "utf8"));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--loader .png=file index.test.ts",
+        0,
+        &format!(
+            "
+                index.test.ts -> works ...
+                {}/fixtures/image.png
+                not really a png
+                index.test.ts -> works PASSED
+                Ran 1 test, 1 passed, 0 failed.
+            ",
+            context.temp_dir.path().to_string_lossy()
+        ),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn unsupported_file_types_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    context.write("query.graphql", "query { foo }\n")?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import query from "./query.graphql";
+            it("works", () => {});
+        "#,
+    )?;
+    let stderr = strip_ansi(&context.run_assert_stderr("index.test.ts", 1))?;
+    assert_contains(&stderr, r#"No loader is configured for ".graphql" files"#);
+    assert_contains(&stderr, r#"--loader .graphql=text"#);
+    assert!(!stderr.contains("index.test.ts -> works"));
+    Ok(())
+}
+
+#[test]
+fn unknown_loaders_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    context.run_assert(
+        "--loader .graphql=foo index.test.ts",
+        1,
+        "
+            error: unsupported loader for .graphql: foo (supported loaders: base64, binary, dataurl, file, js, json, jsx, text, ts, tsx)
+        ",
+    )?;
+    Ok(())
+}
@@ -0,0 +1,275 @@
+mod common;
+
+use anyhow::Result;
+use common::Context;
+
+#[test]
+fn mock_functions_record_calls_and_results() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq, mockFn } from "str";
+            it("works", () => {
+                const f = mockFn((a: number, b: number) => a + b);
+                assertEq(f(1, 2), 3);
+                f(3, 4);
+                console.error(JSON.stringify(f.calls));
+                console.error(JSON.stringify(f.results));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> works ...
+            [[1,2],[3,4]]
+            [{"type":"return","value":3},{"type":"return","value":7}]
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn mock_functions_record_thrown_errors() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockFn } from "str";
+            it("works", () => {
+                const f = mockFn(() => {
+                    throw new Error("foo");
+                });
+                try {
+                    f();
+                } catch (e) {}
+                const result = f.results[0];
+                console.error(result.type, result.type === "throw" && `${result.error}`);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            throw Error: foo
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn mock_implementations_can_be_changed() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockFn } from "str";
+            it("works", async () => {
+                const f = mockFn<(x: number) => unknown>();
+                console.error(f(1));
+                f.mockReturnValue("return value");
+                console.error(f(2));
+                f.mockImplementation((x) => x * 10);
+                console.error(f(3));
+                f.mockResolvedValue("resolved value");
+                console.error(await f(4));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            undefined
+            return value
+            30
+            resolved value
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn mock_assertions() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockFn, assertCalled, assertCalledTimes, assertCalledWith } from "str";
+            it("works", () => {
+                const f = mockFn((a: number, b: { c: string }) => {});
+                f(1, { c: "foo" });
+                f(2, { c: "bar" });
+                assertCalled(f);
+                assertCalledTimes(f, 2);
+                assertCalledWith(f, 2, { c: "bar" });
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn failing_mock_assertions() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockFn, assertCalled, assertCalledTimes, assertCalledWith } from "str";
+            it("not called", () => {
+                assertCalled(mockFn());
+            });
+            it("called too often", () => {
+                const f = mockFn();
+                f();
+                f();
+                assertCalledTimes(f, 1);
+            });
+            it("called with other arguments", () => {
+                const f = mockFn();
+                f(1, 3);
+                assertCalledWith(f, 1, 2);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        1,
+        "
+            index.test.ts -> not called ...
+            expected mock to be called, but it wasn't
+            index.test.ts -> not called FAILED
+            index.test.ts -> called too often ...
+            expected mock to be called 1 time(s), but it was called 2 time(s)
+            index.test.ts -> called too often FAILED
+            index.test.ts -> called with other arguments ...
+            expected mock to be called with:
+                [ 1, 2 ]
+            but it was called with:
+                [ 1, 3 ]
+            index.test.ts -> called with other arguments FAILED
+            Ran 3 tests, 0 passed, 3 failed.
+
+            Failures:
+
+            index.test.ts -> not called
+                expected mock to be called, but it wasn't
+                at index.test.ts:4:17
+
+            index.test.ts -> called too often
+                expected mock to be called 1 time(s), but it was called 2 time(s)
+                at index.test.ts:10:17
+
+            index.test.ts -> called with other arguments
+                expected mock to be called with:
+                    [ 1, 2 ]
+                but it was called with:
+                    [ 1, 3 ]
+                at index.test.ts:15:17
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn spies_call_through_and_are_restored_after_each_test() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, spyOn, assertCalledWith } from "str";
+            const greeter = {
+                greeting: "hello",
+                greet(name: string): string {
+                    return `${this.greeting} ${name}`;
+                },
+            };
+            it("calls through", () => {
+                const spy = spyOn(greeter, "greet");
+                console.error(greeter.greet("a"));
+                assertCalledWith(spy, "a");
+            });
+            it("can be mocked", () => {
+                spyOn(greeter, "greet").mockReturnValue("mocked");
+                console.error(greeter.greet("b"));
+            });
+            it("is restored", () => {
+                console.error(greeter.greet("c"));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> calls through ...
+            hello a
+            index.test.ts -> calls through PASSED
+            index.test.ts -> can be mocked ...
+            mocked
+            index.test.ts -> can be mocked PASSED
+            index.test.ts -> is restored ...
+            hello c
+            index.test.ts -> is restored PASSED
+            Ran 3 tests, 3 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn spies_from_before_each_are_restored_after_the_test() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, describe, beforeEach, spyOn } from "str";
+            const greeter = { greet: (name: string) => `hello ${name}` };
+            describe("mocked", () => {
+                beforeEach(() => {
+                    spyOn(greeter, "greet").mockReturnValue("mocked");
+                });
+                it("a", () => {
+                    console.error(greeter.greet("a"));
+                });
+            });
+            it("b", () => {
+                console.error(greeter.greet("b"));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> mocked -> a ...
+            mocked
+            index.test.ts -> mocked -> a PASSED
+            index.test.ts -> b ...
+            hello b
+            index.test.ts -> b PASSED
+            Ran 2 tests, 2 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
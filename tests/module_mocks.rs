@@ -0,0 +1,410 @@
+mod common;
+
+use anyhow::Result;
+use common::{assert_contains, strip_ansi, Context};
+
+fn write_http_client(context: &Context) -> Result<()> {
+    context.write(
+        "http.ts",
+        r#"
+            export function get(url: string): string {
+                return `real response from ${url}`;
+            }
+        "#,
+    )?;
+    context.write(
+        "client.ts",
+        r#"
+            import { get } from "./http";
+            export function fetchData(): string {
+                return get("/data");
+            }
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn local_modules_can_be_mocked() -> Result<()> {
+    let context = Context::new()?;
+    write_http_client(&context)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockModule } from "str";
+            import { fetchData } from "./client";
+            mockModule("./http", () => ({
+                get: (url: string) => `fake response from ${url}`,
+            }));
+            it("works", () => {
+                console.error(fetchData());
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            fake response from /data
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn mocks_only_apply_to_the_mocking_test_file() -> Result<()> {
+    let context = Context::new()?;
+    write_http_client(&context)?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it, mockModule } from "str";
+            import { fetchData } from "./client";
+            mockModule("./http", () => ({ get: () => "fake response" }));
+            it("a", () => {
+                console.error(fetchData());
+            });
+        "#,
+    )?;
+    context.write(
+        "b.test.ts",
+        r#"
+            import { it } from "str";
+            import { fetchData } from "./client";
+            it("b", () => {
+                console.error(fetchData());
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "a.test.ts b.test.ts",
+        0,
+        "
+            a.test.ts -> a ...
+            fake response
+            a.test.ts -> a PASSED
+            b.test.ts -> b ...
+            real response from /data
+            b.test.ts -> b PASSED
+            Ran 2 tests, 2 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn mock_functions_can_be_used_in_factories() -> Result<()> {
+    let context = Context::new()?;
+    write_http_client(&context)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockModule, mockFn, assertCalledWith, Mock } from "str";
+            import { fetchData } from "./client";
+            import { get } from "./http";
+            mockModule("./http", () => ({
+                get: mockFn().mockReturnValue("fake response"),
+            }));
+            it("works", () => {
+                console.error(fetchData());
+                assertCalledWith(get as Mock, "/data");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            fake response
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn factories_can_use_dirname() -> Result<()> {
+    let context = Context::new()?;
+    write_http_client(&context)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockModule } from "str";
+            import { fetchData } from "./client";
+            mockModule("./http", () => ({
+                get: (url: string) => `${url} from ${__dirname}`,
+            }));
+            it("works", () => {
+                console.error(fetchData());
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        &format!(
+            "
+                index.test.ts -> works ...
+                /data from {}
+                index.test.ts -> works PASSED
+                Ran 1 test, 1 passed, 0 failed.
+            ",
+            context.temp_dir.path().to_string_lossy(),
+        ),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn factories_can_shadow_test_file_variables() -> Result<()> {
+    let context = Context::new()?;
+    write_http_client(&context)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockModule } from "str";
+            import { fetchData } from "./client";
+            const response = "test file response";
+            mockModule("./http", () => {
+                const response = "fake response";
+                return { response, get: () => response };
+            });
+            it("works", () => {
+                console.error(fetchData());
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            fake response
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn factories_using_test_file_imports_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    write_http_client(&context)?;
+    context.write(
+        "fake-http.ts",
+        r#"
+            export const fakeGet = () => "fake response";
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockModule } from "str";
+            import { fetchData } from "./client";
+            import { fakeGet } from "./fake-http";
+            mockModule("./http", () => ({ get: fakeGet }));
+            it("works", () => {
+                console.error(fetchData());
+            });
+        "#,
+    )?;
+    let stderr = strip_ansi(&context.run_assert_stderr("index.test.ts", 1))?;
+    assert_contains(
+        &stderr,
+        r#"mockModule: the factory can't use "fakeGet" from the test file, since it runs before the test file (use mockFn, spyOn or globals instead)"#,
+    );
+    assert_contains(&stderr, "index.test.ts:5:12:");
+    assert!(!stderr.contains("index.test.ts -> works"));
+    Ok(())
+}
+
+#[test]
+fn unparseable_mock_module_calls_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    write_http_client(&context)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockModule } from "str";
+            import { fetchData } from "./client";
+            const http = "./http";
+            mockModule(http, () => ({ get: () => "fake response" }));
+            it("works", () => {
+                console.error(fetchData());
+            });
+        "#,
+    )?;
+    let stderr = strip_ansi(&context.run_assert_stderr("index.test.ts", 1))?;
+    assert_contains(
+        &stderr,
+        "mockModule: the module has to be given as a string literal",
+    );
+    assert_contains(&stderr, "index.test.ts:5:12:");
+    assert!(!stderr.contains("index.test.ts -> works"));
+    Ok(())
+}
+
+#[test]
+fn mock_module_calls_in_comments_and_strings_are_ignored() -> Result<()> {
+    let context = Context::new()?;
+    write_http_client(&context)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { fetchData } from "./client";
+            // mockModule("./http", () => ({ get: () => "fake response" }));
+            /* mockModule("./http", () => ({ get: () => "fake response" })); */
+            const example = 'mockModule("./http", () => ({}))';
+            it("works", () => {
+                console.error(fetchData());
+                console.error(`${example.length} ${/mockModule\(/.test(example)}`);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            real response from /data
+            32 true
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn node_modules_packages_can_be_mocked() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "node_modules/foo/package.json",
+        r#"
+            {
+                "name": "foo",
+                "main": "index.js"
+            }
+        "#,
+    )?;
+    context.write(
+        "node_modules/foo/index.js",
+        r#"
+            module.exports = { foo: () => "real foo" };
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockModule } from "str";
+            import { foo } from "foo";
+            mockModule("foo", () => ({ foo: () => "fake foo" }));
+            it("works", () => {
+                console.error(foo());
+            });
+        "#,
+    )?;
+    context.write(
+        "other.test.ts",
+        r#"
+            import { it } from "str";
+            import { foo } from "foo";
+            it("works", () => {
+                console.error(foo());
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts other.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            fake foo
+            index.test.ts -> works PASSED
+            other.test.ts -> works ...
+            real foo
+            other.test.ts -> works PASSED
+            Ran 2 tests, 2 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn module_mocks_with_format_esm() -> Result<()> {
+    let context = Context::new()?;
+    write_http_client(&context)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, mockModule, mockFn } from "str";
+            import { fetchData } from "./client";
+            mockModule("./http", () => ({ get: mockFn(() => "fake response") }));
+            it("works", () => {
+                console.error(fetchData());
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--format esm index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            fake response
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn module_mocks_can_be_declared_in_the_config() -> Result<()> {
+    let context = Context::new()?;
+    write_http_client(&context)?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "moduleMocks": { "./http": "./fake-http.ts" }
+            }
+        "#,
+    )?;
+    context.write(
+        "fake-http.ts",
+        r#"
+            import { get as realGet } from "./http";
+            export function get(url: string): string {
+                return `fake, instead of: ${realGet(url)}`;
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { fetchData } from "./client";
+            it("works", () => {
+                console.error(fetchData());
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            fake, instead of: real response from /data
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
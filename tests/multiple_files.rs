@@ -75,6 +75,73 @@ fn with_failures() -> Result<()> {
             c.test.ts -> c ...
             c.test.ts -> c PASSED
             Ran 4 tests, 3 passed, 1 failed.
+
+            Failures:
+
+            b.test.ts -> failing
+                EXCEPTION: foo
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn failures_are_recapped_after_the_summary() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            it("a", () => {
+                assertEq(1, 2);
+            });
+        "#,
+    )?;
+    context.write(
+        "b.test.ts",
+        r#"
+            import { it } from "str";
+            it("b", () => {});
+        "#,
+    )?;
+    context.write(
+        "c.test.ts",
+        r#"
+            import { it, describe } from "str";
+            describe("nested", () => {
+                it("c", () => {
+                    throw new Error("c failed");
+                });
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "a.test.ts b.test.ts c.test.ts",
+        1,
+        "
+            a.test.ts -> a ...
+            1
+                !==
+            2
+            a.test.ts -> a FAILED
+            b.test.ts -> b ...
+            b.test.ts -> b PASSED
+            c.test.ts -> nested -> c ...
+            EXCEPTION: Error: c failed
+            c.test.ts -> nested -> c FAILED
+            Ran 3 tests, 1 passed, 2 failed.
+
+            Failures:
+
+            a.test.ts -> a
+                1
+                    !==
+                2
+                at a.test.ts:4:17
+
+            c.test.ts -> nested -> c
+                EXCEPTION: Error: c failed
+                at c.test.ts:5:27
         ",
     )?;
     Ok(())
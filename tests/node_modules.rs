@@ -102,3 +102,107 @@ fn dynamically_generated_import_paths_in_node_modules() -> Result<()> {
     )?;
     Ok(())
 }
+
+#[test]
+fn esm_only_dependencies_with_format_esm() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "node_modules/foo/package.json",
+        r#"
+            {
+                "name": "foo",
+                "type": "module",
+                "exports": "./src/index.js"
+            }
+        "#,
+    )?;
+    context.write(
+        "node_modules/foo/src/index.js",
+        r#"
+            export function foo() {
+                console.error("foo called");
+            }
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { foo } from "foo";
+            it("works", () => {
+                foo();
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--format esm index.test.ts",
+        0,
+        r#"
+            index.test.ts -> works ...
+            foo called
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn conditional_exports_are_resolved_with_esm_resolution() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "node_modules/foo/package.json",
+        r#"
+            {
+                "name": "foo",
+                "exports": {
+                    "import": "./index.mjs",
+                    "require": "./index.cjs"
+                }
+            }
+        "#,
+    )?;
+    context.write(
+        "node_modules/foo/index.mjs",
+        r#"
+            export const format = "esm";
+        "#,
+    )?;
+    context.write(
+        "node_modules/foo/index.cjs",
+        r#"
+            exports.format = "cjs";
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { format } from "foo";
+            it("works", () => {
+                console.error(format);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--format esm index.test.ts",
+        0,
+        r#"
+            index.test.ts -> works ...
+            esm
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    context.run_assert(
+        "--format cjs index.test.ts",
+        0,
+        r#"
+            index.test.ts -> works ...
+            cjs
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
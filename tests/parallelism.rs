@@ -0,0 +1,239 @@
+mod common;
+
+use anyhow::Result;
+use common::{assert_contains, replace_durations, strip_ansi, Context};
+use pretty_assertions::assert_eq;
+use unindent::Unindent;
+
+fn write_test_file(context: &Context, name: &str, body: &str) -> Result<()> {
+    context.write(
+        format!("{}.test.ts", name),
+        &format!(
+            r#"
+                import * as fs from "fs";
+                import {{ it, globalSetupData }} from "str";
+                it("{}", () => {{
+                    fs.mkdirSync("pids", {{ recursive: true }});
+                    fs.writeFileSync("pids/{}", String(process.pid));
+                    {}
+                }});
+            "#,
+            name, name, body
+        ),
+    )?;
+    Ok(())
+}
+
+fn run(context: &Context, args: &str, expected_exit_code: i32) -> Result<String> {
+    Ok(replace_durations(
+        &strip_ansi(&context.run_assert_stderr(args, expected_exit_code))?,
+        "",
+    ))
+}
+
+#[test]
+fn test_files_run_in_parallel_workers() -> Result<()> {
+    let context = Context::new()?;
+    for name in ["a", "b", "c"] {
+        write_test_file(&context, name, "")?;
+    }
+    let output = run(&context, "--parallelism 2 a.test.ts b.test.ts c.test.ts", 0)?;
+    for name in ["a", "b", "c"] {
+        assert_contains(&output, format!("{}.test.ts -> {} PASSED", name, name));
+    }
+    assert!(output.ends_with("Ran 3 tests, 3 passed, 0 failed.\n"));
+    // The files are split into consecutive chunks: `a` and `b, c`.
+    assert_ne!(context.read("pids/a")?, context.read("pids/b")?);
+    assert_eq!(context.read("pids/b")?, context.read("pids/c")?);
+    Ok(())
+}
+
+#[test]
+fn parallelism_can_be_configured() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "testFiles": ["*.test.ts"],
+                "parallelism": 2
+            }
+        "#,
+    )?;
+    for name in ["a", "b"] {
+        write_test_file(&context, name, "")?;
+    }
+    run(&context, "", 0)?;
+    assert_ne!(context.read("pids/a")?, context.read("pids/b")?);
+    Ok(())
+}
+
+#[test]
+fn global_setup_and_teardown_run_once_with_parallel_workers() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "global-setup.ts",
+        r#"
+            export default () => {
+                console.error("global setup");
+                return { port: 4242 };
+            };
+        "#,
+    )?;
+    context.write(
+        "global-teardown.ts",
+        r#"
+            export default (data) => {
+                console.error(`global teardown: ${data.port}`);
+            };
+        "#,
+    )?;
+    context.write("setup.ts", r#"console.error("setup file");"#)?;
+    for name in ["a", "b"] {
+        write_test_file(
+            &context,
+            name,
+            "console.error(`port: ${globalSetupData<{ port: number }>().port}`);",
+        )?;
+    }
+    let output = run(
+        &context,
+        "--parallelism 2 --global-setup global-setup.ts --global-teardown global-teardown.ts --setup setup.ts a.test.ts b.test.ts",
+        0,
+    )?;
+    assert_eq!(output.matches("global setup").count(), 1);
+    assert_eq!(output.matches("setup file").count(), 2);
+    assert_eq!(output.matches("port: 4242").count(), 2);
+    assert!(output.ends_with(
+        &"
+            global teardown: 4242
+            Ran 2 tests, 2 passed, 0 failed.
+        "
+        .unindent()
+    ));
+    Ok(())
+}
+
+#[test]
+fn failures_in_workers_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    write_test_file(&context, "a", "")?;
+    write_test_file(&context, "b", r#"throw "foo";"#)?;
+    let output = run(&context, "--parallelism 2 a.test.ts b.test.ts", 1)?;
+    assert!(output.ends_with(
+        &"
+            Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            b.test.ts -> b
+                EXCEPTION: foo
+        "
+        .unindent()
+    ));
+    Ok(())
+}
+
+#[test]
+fn crashing_workers_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    write_test_file(&context, "a", "")?;
+    write_test_file(&context, "b", "process.exit(3);")?;
+    let output = run(&context, "--parallelism 2 a.test.ts b.test.ts", 1)?;
+    assert!(output.ends_with(
+        &"
+            Ran 2 tests, 1 passed, 1 failed.
+
+            Failures:
+
+            b.test.ts
+                worker exited with code 3 before reporting results
+        "
+        .unindent()
+    ));
+    Ok(())
+}
+
+#[test]
+fn crashing_workers_fail_all_their_test_files() -> Result<()> {
+    let context = Context::new()?;
+    write_test_file(&context, "a", "")?;
+    write_test_file(&context, "b", "process.exit(3);")?;
+    write_test_file(&context, "c", "")?;
+    let output = run(
+        &context,
+        "--parallelism 2 --json-report report.json a.test.ts b.test.ts c.test.ts",
+        1,
+    )?;
+    assert!(output.ends_with(
+        &"
+            Ran 3 tests, 1 passed, 2 failed.
+
+            Failures:
+
+            b.test.ts
+                worker exited with code 3 before reporting results
+
+            c.test.ts
+                worker exited with code 3 before reporting results
+        "
+        .unindent()
+    ));
+    let report = context.read("report.json")?;
+    assert_contains(&report, r#""name": "b.test.ts","#);
+    assert_contains(&report, r#""name": "c.test.ts","#);
+    Ok(())
+}
+
+#[test]
+fn output_of_workers_does_not_interleave() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", async () => {
+                console.error("a starts");
+                await new Promise((resolve) => setTimeout(resolve, 500));
+                console.error("a ends");
+            });
+        "#,
+    )?;
+    context.write(
+        "b.test.ts",
+        r#"
+            import { it } from "str";
+            it("b", () => {
+                console.error("b runs");
+            });
+        "#,
+    )?;
+    let output = run(&context, "--parallelism 2 a.test.ts b.test.ts", 0)?;
+    assert_contains(
+        &output,
+        "
+            a.test.ts -> a ...
+            a starts
+            a ends
+            a.test.ts -> a PASSED
+            b.test.ts -> b ...
+            b runs
+            b.test.ts -> b PASSED
+        "
+        .unindent(),
+    );
+    Ok(())
+}
+
+#[test]
+fn negative_parallelism_is_reported() -> Result<()> {
+    let context = Context::new()?;
+    context.run_assert(
+        "--parallelism -1 index.test.ts",
+        1,
+        "
+            error: parallelism must not be negative: -1
+        ",
+    )?;
+    Ok(())
+}
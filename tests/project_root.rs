@@ -0,0 +1,196 @@
+mod common;
+
+use anyhow::Result;
+use common::Context;
+
+fn project_context() -> Result<Context> {
+    let context = Context::new()?;
+    context.write("package.json", r#"{ "name": "project" }"#)?;
+    context.write(
+        "src/foo.test.ts",
+        r#"
+            import { it } from "str";
+            it("works", () => {});
+        "#,
+    )?;
+    Ok(context)
+}
+
+#[test]
+fn test_files_are_reported_relative_to_the_project_root() -> Result<()> {
+    let mut context = project_context()?;
+    context.cd("src");
+    context.run_assert(
+        "foo.test.ts",
+        0,
+        "
+            src/foo.test.ts -> works ...
+            src/foo.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn parent_directories_in_test_paths() -> Result<()> {
+    let mut context = project_context()?;
+    context.write(
+        "test/bar.test.ts",
+        r#"
+            import { it } from "str";
+            it("works", () => {});
+        "#,
+    )?;
+    context.cd("src");
+    context.run_assert(
+        "../test/bar.test.ts",
+        0,
+        "
+            test/bar.test.ts -> works ...
+            test/bar.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn absolute_test_paths() -> Result<()> {
+    let mut context = project_context()?;
+    let file = context.temp_dir.path().join("src/foo.test.ts");
+    context.cd("src");
+    context.run_assert(
+        &file.to_string_lossy(),
+        0,
+        "
+            src/foo.test.ts -> works ...
+            src/foo.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn the_config_file_is_found_from_subdirectories() -> Result<()> {
+    let mut context = project_context()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "testFiles": ["src/**/*.test.ts"],
+                "env": { "FOO": "from the config file" }
+            }
+        "#,
+    )?;
+    context.write(
+        "src/foo.test.ts",
+        r#"
+            import { it } from "str";
+            it("works", () => {
+                console.error(process.env.FOO);
+            });
+        "#,
+    )?;
+    context.cd("src");
+    context.run_assert(
+        "",
+        0,
+        "
+            src/foo.test.ts -> works ...
+            from the config file
+            src/foo.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn path_flags_are_relative_to_the_current_directory() -> Result<()> {
+    let mut context = project_context()?;
+    context.write(
+        "src/setup.ts",
+        r#"
+            console.error("setup");
+        "#,
+    )?;
+    context.cd("src");
+    context.run_assert(
+        "--setup setup.ts foo.test.ts",
+        0,
+        "
+            setup
+            src/foo.test.ts -> works ...
+            src/foo.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn tests_run_in_the_current_directory() -> Result<()> {
+    let mut context = project_context()?;
+    context.write(
+        "src/foo.test.ts",
+        r#"
+            import { it } from "str";
+            it("works", () => {
+                console.error(process.cwd());
+            });
+        "#,
+    )?;
+    context.cd("src");
+    context.run_assert(
+        "foo.test.ts",
+        0,
+        &format!(
+            "
+                src/foo.test.ts -> works ...
+                {}/src
+                src/foo.test.ts -> works PASSED
+                Ran 1 test, 1 passed, 0 failed.
+            ",
+            context.temp_dir.path().to_string_lossy()
+        ),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn failure_locations_are_relative_to_the_project_root() -> Result<()> {
+    let mut context = project_context()?;
+    context.write(
+        "src/foo.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            it("fails", () => {
+                assertEq(true, false);
+            });
+        "#,
+    )?;
+    context.cd("src");
+    context.run_assert(
+        "foo.test.ts",
+        1,
+        "
+            src/foo.test.ts -> fails ...
+            true
+                !==
+            false
+            src/foo.test.ts -> fails FAILED
+            Ran 1 test, 0 passed, 1 failed.
+
+            Failures:
+
+            src/foo.test.ts -> fails
+                true
+                    !==
+                false
+                at src/foo.test.ts:4:17
+        ",
+    )?;
+    Ok(())
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use anyhow::Result;
+use common::{assert_contains, strip_ansi, Context};
+
+#[test]
+fn passing_properties() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { property, gen } from "str";
+            property("addition is commutative", [gen.int(), gen.int()], (a, b) => a + b === b + a);
+            property("filter and map", [gen.int(0, 100).filter((n) => n % 2 === 0).map((n) => n + 1)], (n) => n % 2 === 1);
+            property("oneOf", [gen.oneOf<number | string>(gen.int(), gen.string())], (x) =>
+                typeof x === "number" || typeof x === "string"
+            );
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> addition is commutative ...
+            index.test.ts -> addition is commutative PASSED
+            index.test.ts -> filter and map ...
+            index.test.ts -> filter and map PASSED
+            index.test.ts -> oneOf ...
+            index.test.ts -> oneOf PASSED
+            Ran 3 tests, 3 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn failing_ints_are_shrunk() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { property, gen } from "str";
+            property("small", [gen.int(0, 1000)], (n) => n < 10);
+        "#,
+    )?;
+    let stderr = context.run_assert_stderr("--seed 42 index.test.ts", 1);
+    assert_contains(&stderr, "(rerun with --seed 42)");
+    assert_contains(&stderr, "): [ 10 ]\npredicate returned false");
+    Ok(())
+}
+
+#[test]
+fn failing_arrays_are_shrunk() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { property, gen } from "str";
+            property("short", [gen.array(gen.int(0, 100))], (xs) => xs.length < 3);
+        "#,
+    )?;
+    let stderr = context.run_assert_stderr("index.test.ts", 1);
+    assert_contains(stderr, "): [ [ 0, 0, 0 ] ]");
+    Ok(())
+}
+
+#[test]
+fn failing_records_are_shrunk() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { property, gen } from "str";
+            property(
+                "adults have names",
+                [gen.record({ name: gen.string(), age: gen.int(0, 120) })],
+                (person) => person.age < 18 || person.name.length > 0
+            );
+        "#,
+    )?;
+    let stderr = context.run_assert_stderr("index.test.ts", 1);
+    assert_contains(stderr, "): [ { name: '', age: 18 } ]");
+    Ok(())
+}
+
+#[test]
+fn failing_assertions_are_reported_with_their_location() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { property, gen, assertEq } from "str";
+            property("doubling", [gen.int(0, 1000)], (n) => {
+                assertEq(n * 2, n < 5 ? n + n : n);
+            });
+        "#,
+    )?;
+    let stderr = strip_ansi(&context.run_assert_stderr("index.test.ts", 1))?;
+    assert_contains(
+        &stderr,
+        "index.test.ts -> doubling\n    property failed after",
+    );
+    assert_contains(
+        &stderr,
+        "): [ 5 ]\n    10\n        !==\n    5\n    at index.test.ts:4:17",
+    );
+    Ok(())
+}
+
+#[test]
+fn the_printed_seed_reproduces_failures() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { property, gen } from "str";
+            property("small", [gen.int(0, 1000000)], (n) => {
+                console.error(`checking ${n}`);
+                return n < 10;
+            });
+        "#,
+    )?;
+    let first = context.run_assert_stderr("index.test.ts", 1);
+    let seed = first
+        .split("rerun with --seed ")
+        .nth(1)
+        .and_then(|rest| rest.split(')').next())
+        .expect("seed not found");
+    let second = context.run_assert_stderr(&format!("--seed {} index.test.ts", seed), 1);
+    let checked = |output: &str| {
+        output
+            .lines()
+            .filter(|line| line.starts_with("checking"))
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(checked(&first), checked(&second));
+    Ok(())
+}
+
+#[test]
+fn seed_and_runs_can_be_given_as_options() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { property, gen } from "str";
+            let runs = 0;
+            property("counts", [gen.int()], () => {
+                runs++;
+            }, { runs: 7, seed: 1 });
+            property("reports", [gen.int()], () => runs !== 7, { seed: 1 });
+        "#,
+    )?;
+    let stderr = context.run_assert_stderr("index.test.ts", 1);
+    assert_contains(
+        stderr,
+        "property failed after 1 run(s) (rerun with --seed 1)",
+    );
+    Ok(())
+}
@@ -0,0 +1,251 @@
+mod common;
+
+use anyhow::Result;
+use common::{assert_contains, Context};
+use pretty_assertions::assert_eq;
+use unindent::Unindent;
+
+/// Removes the `"duration"` lines, since durations vary between runs.
+fn without_durations(report: &str) -> String {
+    report
+        .lines()
+        .filter(|line| !line.contains(r#""duration""#))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn write_tests(context: &Context) -> Result<()> {
+    context.write(
+        "index.test.ts",
+        r#"
+            import { describe, it, xit } from "str";
+            describe("app", () => {
+                it("works", () => {});
+                it("fails", () => {
+                    throw "foo";
+                });
+                xit("later", () => {});
+            });
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn writes_a_json_report() -> Result<()> {
+    let context = Context::new()?;
+    write_tests(&context)?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "reporters": { "json": "reports/str.json" }
+            }
+        "#,
+    )?;
+    context.run_assert_stderr("index.test.ts", 1);
+    assert_eq!(
+        without_durations(&context.read("reports/str.json")?),
+        r#"
+            {
+              "summary": {
+                "tests": 2,
+                "passed": 1,
+                "failed": 1,
+                "xfail": 0,
+                "ignored": 1,
+                "skipped": 0,
+                "todo": 0
+              },
+              "tests": [
+                {
+                  "name": "index.test.ts -> app -> works",
+                  "path": [
+                    "index.test.ts",
+                    "app",
+                    "works"
+                  ],
+                  "status": "passed",
+                },
+                {
+                  "name": "index.test.ts -> app -> fails",
+                  "path": [
+                    "index.test.ts",
+                    "app",
+                    "fails"
+                  ],
+                  "status": "failed",
+                  "message": "EXCEPTION: foo"
+                },
+                {
+                  "name": "index.test.ts -> app -> later",
+                  "path": [
+                    "index.test.ts",
+                    "app",
+                    "later"
+                  ],
+                  "status": "ignored"
+                }
+              ]
+            }
+        "#
+        .unindent()
+    );
+    Ok(())
+}
+
+#[test]
+fn writes_a_junit_report() -> Result<()> {
+    let context = Context::new()?;
+    write_tests(&context)?;
+    context.run_assert_stderr("--junit-report junit.xml index.test.ts", 1);
+    let report = context.read("junit.xml")?;
+    assert_contains(&report, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    assert_contains(
+        &report,
+        r#"<testsuites name="str" tests="3" failures="1" skipped="1" time=""#,
+    );
+    assert_contains(
+        &report,
+        r#"<testsuite name="index.test.ts" tests="3" failures="1" skipped="1" time=""#,
+    );
+    assert_contains(
+        &report,
+        r#"<testcase classname="index.test.ts" name="app -> works" time=""#,
+    );
+    assert_contains(
+        &report,
+        r#"<failure message="EXCEPTION: foo">EXCEPTION: foo</failure>"#,
+    );
+    assert_contains(&report, r#"<skipped message="ignored"/>"#);
+    Ok(())
+}
+
+#[test]
+fn junit_reports_are_escaped() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("<a> & 'b'", () => {
+                throw new Error("expected <div>");
+            });
+        "#,
+    )?;
+    context.run_assert_stderr("--junit-report junit.xml index.test.ts", 1);
+    let report = context.read("junit.xml")?;
+    assert_contains(&report, r#"name="&lt;a&gt; &amp; &apos;b&apos;""#);
+    assert_contains(&report, "EXCEPTION: Error: expected &lt;div&gt;");
+    Ok(())
+}
+
+#[test]
+fn report_flags_are_relative_to_the_current_directory() -> Result<()> {
+    let mut context = Context::new()?;
+    context.write("package.json", "{}")?;
+    context.write(
+        "src/index.test.ts",
+        r#"
+            import { it } from "str";
+            it("works", () => {});
+        "#,
+    )?;
+    context.cd("src");
+    context.run_assert_stderr("--json-report reports/str.json index.test.ts", 0);
+    assert_contains(context.read("src/reports/str.json")?, r#""passed": 1,"#);
+    Ok(())
+}
+
+#[test]
+fn report_files_in_the_config_are_relative_to_the_project_root() -> Result<()> {
+    let mut context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "reporters": { "json": "reports/str.json" }
+            }
+        "#,
+    )?;
+    context.write(
+        "src/index.test.ts",
+        r#"
+            import { it } from "str";
+            it("works", () => {});
+        "#,
+    )?;
+    context.cd("src");
+    context.run_assert_stderr("index.test.ts", 0);
+    assert_contains(context.read("reports/str.json")?, r#""passed": 1,"#);
+    Ok(())
+}
+
+#[test]
+fn unsupported_reporters_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "reporters": { "html": "report.html" }
+            }
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        1,
+        "
+            error: unsupported reporter: html (supported reporters: json, junit)
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn notes_are_written_to_the_reports() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", (ctx) => {
+                ctx.note("issue", "PROJ-123");
+                ctx.note("attempts", { count: 3 });
+            });
+        "#,
+    )?;
+    context.run_assert_stderr(
+        "--json-report report.json --junit-report junit.xml index.test.ts",
+        0,
+    );
+    assert_contains(
+        without_durations(&context.read("report.json")?),
+        r#"
+                  "notes": {
+                    "issue": "PROJ-123",
+                    "attempts": {
+                      "count": 3
+                    }
+                  }
+        "#
+        .unindent()
+        .lines()
+        .map(|line| format!("      {}\n", line))
+        .collect::<String>(),
+    );
+    assert_contains(
+        context.read("junit.xml")?,
+        r#"
+            <properties>
+              <property name="issue" value="PROJ-123"/>
+              <property name="attempts" value="{&quot;count&quot;:3}"/>
+            </properties>
+        "#
+        .unindent()
+        .lines()
+        .map(|line| format!("      {}\n", line))
+        .collect::<String>(),
+    );
+    Ok(())
+}
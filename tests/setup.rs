@@ -0,0 +1,263 @@
+mod common;
+
+use anyhow::Result;
+use common::{assert_contains, strip_ansi, Context};
+
+#[test]
+fn setup_files_run_before_test_files() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "setup.ts",
+        r#"
+            console.error("setup");
+            (globalThis as any).polyfill = () => "polyfilled";
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            console.error("test file");
+            it("a", () => {
+                console.error((globalThis as any).polyfill());
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--setup setup.ts index.test.ts",
+        0,
+        "
+            setup
+            test file
+            index.test.ts -> a ...
+            polyfilled
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn setup_files_run_once_before_all_test_files() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "setupFiles": ["setup/a.ts", "setup/b.ts"]
+            }
+        "#,
+    )?;
+    context.write("setup/a.ts", r#"console.error("setup a");"#)?;
+    context.write("setup/b.ts", r#"console.error("setup b");"#)?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it } from "str";
+            console.error("test file a");
+            it("a", () => {});
+        "#,
+    )?;
+    context.write(
+        "b.test.ts",
+        r#"
+            import { it } from "str";
+            console.error("test file b");
+            it("b", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "a.test.ts b.test.ts",
+        0,
+        "
+            setup a
+            setup b
+            test file a
+            test file b
+            a.test.ts -> a ...
+            a.test.ts -> a PASSED
+            b.test.ts -> b ...
+            b.test.ts -> b PASSED
+            Ran 2 tests, 2 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn global_setup_and_teardown_run_once() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "global-setup.ts",
+        r#"
+            export default async () => {
+                await null;
+                console.error("global setup");
+            };
+        "#,
+    )?;
+    context.write(
+        "global-teardown.ts",
+        r#"
+            export default async () => {
+                await null;
+                console.error("global teardown");
+            };
+        "#,
+    )?;
+    context.write("setup.ts", r#"console.error("setup");"#)?;
+    context.write(
+        "a.test.ts",
+        r#"
+            import { it, afterAll } from "str";
+            console.error("test file a");
+            it("a", () => {});
+            afterAll(() => console.error("afterAll a"));
+        "#,
+    )?;
+    context.write(
+        "b.test.ts",
+        r#"
+            import { it } from "str";
+            console.error("test file b");
+            it("b", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "--global-setup global-setup.ts --global-teardown global-teardown.ts --setup setup.ts a.test.ts b.test.ts",
+        0,
+        "
+            global setup
+            setup
+            test file a
+            test file b
+            a.test.ts -> a ...
+            a.test.ts -> a PASSED
+            afterAll a
+            b.test.ts -> b ...
+            b.test.ts -> b PASSED
+            global teardown
+            Ran 2 tests, 2 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn global_teardown_runs_when_tests_fail() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "global-teardown.ts",
+        r#"
+            export default () => {
+                console.error("global teardown");
+            };
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("fails", () => {
+                throw "foo";
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--global-teardown global-teardown.ts index.test.ts",
+        1,
+        "
+            index.test.ts -> fails ...
+            EXCEPTION: foo
+            index.test.ts -> fails FAILED
+            global teardown
+            Ran 1 test, 0 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> fails
+                EXCEPTION: foo
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn global_teardown_runs_when_before_all_fails() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "global-teardown.ts",
+        r#"
+            export default () => {
+                console.error("global teardown");
+            };
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, beforeAll } from "str";
+            beforeAll(() => {
+                throw new Error("beforeAll failed");
+            });
+            it("works", () => {});
+        "#,
+    )?;
+    let stderr = strip_ansi(
+        &context.run_assert_stderr("--global-teardown global-teardown.ts index.test.ts", 1),
+    )?;
+    assert_contains(&stderr, "Error: beforeAll failed");
+    assert_contains(&stderr, "global teardown\n");
+    assert!(!stderr.contains("index.test.ts -> works"));
+    Ok(())
+}
+
+#[test]
+fn global_setup_can_pass_data_to_tests() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "str.config.json",
+        r#"
+            {
+                "globalSetup": "global-setup.ts",
+                "globalTeardown": "global-teardown.ts"
+            }
+        "#,
+    )?;
+    context.write(
+        "global-setup.ts",
+        r#"
+            export default () => ({ port: 4242, createdAt: new Date(0) });
+        "#,
+    )?;
+    context.write(
+        "global-teardown.ts",
+        r#"
+            export default (data) => {
+                console.error(`teardown: ${data.port}`);
+            };
+        "#,
+    )?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, globalSetupData } from "str";
+            it("a", () => {
+                console.error(JSON.stringify(globalSetupData()));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> a ...
+            {"port":4242,"createdAt":"1970-01-01T00:00:00.000Z"}
+            index.test.ts -> a PASSED
+            teardown: 4242
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,248 @@
+mod common;
+
+use anyhow::Result;
+use common::Context;
+
+#[test]
+fn tests_receive_their_names() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { describe, it } from "str";
+            describe("math", () => {
+                it("adds", (ctx) => {
+                    console.error(ctx.name);
+                    console.error(ctx.path);
+                    console.error(ctx.fullName);
+                });
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> math -> adds ...
+            adds
+            [ 'index.test.ts', 'math', 'adds' ]
+            index.test.ts -> math -> adds
+            index.test.ts -> math -> adds PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn hooks_receive_the_test_context() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, beforeEach, afterEach } from "str";
+            beforeEach((ctx) => console.error(`before ${ctx.name}`));
+            afterEach((ctx) => console.error(`after ${ctx.name}`));
+            it("a", () => {});
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> a ...
+            before a
+            after a
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn the_signal_is_aborted_on_timeouts() -> Result<()> {
+    let context = Context::new()?;
+    context.write("str.config.json", r#"{ "timeout": 50 }"#)?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("hangs", async (ctx) => {
+                ctx.signal.addEventListener("abort", () => console.error("aborted"));
+                await new Promise(() => {});
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        1,
+        r#"
+            index.test.ts -> hangs ...
+            aborted
+            EXCEPTION: Error: test timed out after 50ms
+            index.test.ts -> hangs FAILED
+            Ran 1 test, 0 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> hangs
+                EXCEPTION: Error: test timed out after 50ms
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn temp_dirs_are_removed_after_the_test() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import * as fs from "fs";
+            import * as path from "path";
+            let tempDir: string;
+            it("writes a file", (ctx) => {
+                tempDir = ctx.tempDir;
+                fs.writeFileSync(path.join(ctx.tempDir, "file"), "content");
+                console.error(fs.readdirSync(ctx.tempDir));
+            });
+            it("checks the temp dir", () => {
+                console.error(fs.existsSync(tempDir));
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> writes a file ...
+            [ 'file' ]
+            index.test.ts -> writes a file PASSED
+            index.test.ts -> checks the temp dir ...
+            false
+            index.test.ts -> checks the temp dir PASSED
+            Ran 2 tests, 2 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn tests_can_skip_themselves() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("needs docker", (ctx) => {
+                ctx.skip("docker is not installed");
+                console.error("not reached");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> needs docker ...
+            index.test.ts -> needs docker SKIPPED (docker is not installed)
+            Ran 0 tests, 0 passed, 0 failed, 1 skipped.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn cleanup_functions_run_after_the_test_in_reverse_order() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, afterEach } from "str";
+            afterEach(() => console.error("afterEach"));
+            it("a", (ctx) => {
+                ctx.onCleanup(() => console.error("cleanup 1"));
+                ctx.onCleanup(async () => console.error("cleanup 2"));
+                console.error("test");
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> a ...
+            test
+            afterEach
+            cleanup 2
+            cleanup 1
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn failing_cleanup_functions_fail_the_test() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", (ctx) => {
+                ctx.onCleanup(() => console.error("cleanup 1"));
+                ctx.onCleanup(() => {
+                    throw new Error("cleanup failed");
+                });
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        1,
+        r#"
+            index.test.ts -> a ...
+            cleanup 1
+            EXCEPTION: Error: cleanup failed
+            index.test.ts -> a FAILED
+            Ran 1 test, 0 passed, 1 failed.
+
+            Failures:
+
+            index.test.ts -> a
+                EXCEPTION: Error: cleanup failed
+                at index.test.ts:6:27
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn notes_are_reported_with_the_result() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", (ctx) => {
+                ctx.note("issue", "PROJ-123");
+                ctx.note("attempts", { count: 3 });
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        r#"
+            index.test.ts -> a ...
+            index.test.ts -> a PASSED
+                issue: PROJ-123
+                attempts: { count: 3 }
+            Ran 1 test, 1 passed, 0 failed.
+        "#,
+    )?;
+    Ok(())
+}
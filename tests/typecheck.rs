@@ -0,0 +1,106 @@
+mod common;
+
+use anyhow::Result;
+use common::{assert_contains, strip_ansi, Context};
+
+#[test]
+fn type_errors_fail_the_run() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {
+                const x: number = "foo";
+            });
+        "#,
+    )?;
+    let stderr = strip_ansi(&context.run_assert_stderr("--typecheck index.test.ts", 1))?;
+    assert_contains(&stderr, "index.test.ts:4:");
+    assert_contains(
+        &stderr,
+        "Type 'string' is not assignable to type 'number'. [TS2322]",
+    );
+    assert_contains(&stderr, r#"const x: number = "foo";"#);
+    assert!(!stderr.contains("index.test.ts -> a"));
+    Ok(())
+}
+
+#[test]
+fn type_errors_in_imported_files_are_reported() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            import { foo } from "./foo";
+            it("a", () => {
+                foo();
+            });
+        "#,
+    )?;
+    context.write(
+        "foo.ts",
+        r#"
+            export function foo(): string {
+                return 42;
+            }
+        "#,
+    )?;
+    let stderr = strip_ansi(&context.run_assert_stderr("--typecheck index.test.ts", 1))?;
+    assert_contains(&stderr, "foo.ts:3:");
+    assert_contains(
+        &stderr,
+        "Type 'number' is not assignable to type 'string'. [TS2322]",
+    );
+    Ok(())
+}
+
+#[test]
+fn well_typed_tests_run_normally() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it, assertEq } from "str";
+            it("a", () => {
+                const x: number = 42;
+                assertEq(x, 42);
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "--typecheck index.test.ts",
+        0,
+        "
+            index.test.ts -> a ...
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn type_errors_are_ignored_without_typecheck() -> Result<()> {
+    let context = Context::new()?;
+    context.write(
+        "index.test.ts",
+        r#"
+            import { it } from "str";
+            it("a", () => {
+                const x: number = "foo";
+            });
+        "#,
+    )?;
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> a ...
+            index.test.ts -> a PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
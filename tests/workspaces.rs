@@ -0,0 +1,178 @@
+mod common;
+
+use anyhow::Result;
+use common::Context;
+
+fn workspace_context() -> Result<Context> {
+    let context = Context::new()?;
+    context.workspace(&[("@org/a", "packages/a"), ("@org/b", "packages/b")])?;
+    context.write(
+        "packages/b/src/index.ts",
+        r#"
+            export function greet(name: string): string {
+                return `hello ${name}`;
+            }
+        "#,
+    )?;
+    context.write(
+        "packages/a/index.test.ts",
+        r#"
+            import { it } from "str";
+            import { greet } from "@org/b";
+            it("works", () => {
+                console.error(greet("a"));
+            });
+        "#,
+    )?;
+    Ok(context)
+}
+
+#[test]
+fn workspace_packages_are_bundled_from_source() -> Result<()> {
+    let mut context = workspace_context()?;
+    context.cd("packages/a");
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            hello a
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn running_from_the_workspace_root() -> Result<()> {
+    let context = workspace_context()?;
+    context.run_assert(
+        "packages/a/index.test.ts",
+        0,
+        "
+            packages/a/index.test.ts -> works ...
+            hello a
+            packages/a/index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn workspace_packages_can_be_imported_by_subpath() -> Result<()> {
+    let mut context = workspace_context()?;
+    context.write(
+        "packages/b/src/utils.ts",
+        r#"
+            export const util: string = "util from b";
+        "#,
+    )?;
+    context.write(
+        "packages/a/index.test.ts",
+        r#"
+            import { it } from "str";
+            import { util } from "@org/b/src/utils";
+            it("works", () => {
+                console.error(util);
+            });
+        "#,
+    )?;
+    context.cd("packages/a");
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            util from b
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn dependencies_are_loaded_from_the_workspace_root() -> Result<()> {
+    let mut context = workspace_context()?;
+    context.write(
+        "node_modules/foo/package.json",
+        r#"
+            {
+                "name": "foo",
+                "main": "index.js"
+            }
+        "#,
+    )?;
+    context.write(
+        "node_modules/foo/index.js",
+        r#"
+            module.exports = { foo: "foo from the root node_modules" };
+        "#,
+    )?;
+    context.write(
+        "packages/b/src/index.ts",
+        r#"
+            import { foo } from "foo";
+            export function greet(name: string): string {
+                return `${foo}, ${name}`;
+            }
+        "#,
+    )?;
+    context.cd("packages/a");
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            foo from the root node_modules, a
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn workspaces_can_be_given_as_an_object() -> Result<()> {
+    let mut context = workspace_context()?;
+    context.write(
+        "package.json",
+        r#"
+            {
+                "private": true,
+                "workspaces": { "packages": ["packages/*"] }
+            }
+        "#,
+    )?;
+    context.cd("packages/a");
+    context.run_assert(
+        "index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            hello a
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn workspace_packages_with_format_esm() -> Result<()> {
+    let mut context = workspace_context()?;
+    context.cd("packages/a");
+    context.run_assert(
+        "--format esm index.test.ts",
+        0,
+        "
+            index.test.ts -> works ...
+            hello a
+            index.test.ts -> works PASSED
+            Ran 1 test, 1 passed, 0 failed.
+        ",
+    )?;
+    Ok(())
+}
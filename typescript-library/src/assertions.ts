@@ -1,9 +1,14 @@
+import { inspect, isDeepStrictEqual } from "util";
+import { each } from "./each";
+import { Mock } from "./mocks";
 import {
   _strTestRunner,
   StrTestFailure,
   newTestTree,
+  Test,
   TestChild,
 } from "./test_tree";
+import { TestContext } from "./test_context";
 
 export function describe(description: string, inner: () => void): void {
   let child: TestChild = {
@@ -17,19 +22,84 @@ export function describe(description: string, inner: () => void): void {
   _strTestRunner._stack.pop();
 }
 
-export function it(testName: string, test: () => void | Promise<void>): void {
-  _strTestRunner._stackCurrent().children.push([testName, { tag: "it", test }]);
+describe.each = each<void>(describe);
+
+export function it(testName: string, test: Test): void {
+  _strTestRunner
+    ._stackCurrent()
+    .children.push([testName, { tag: "it", test, failing: false }]);
 }
 
+it.each = each<void | Promise<void>, [TestContext]>(it);
+
+// Registers a test that isn't written yet.
+it.todo = (testName: string): void => {
+  _strTestRunner._stackCurrent().children.push([testName, { tag: "todo" }]);
+};
+
+// Skips the test if `condition` is true, e.g.
+// `it.skipIf(process.platform === "win32")("uses symlinks", () => ...)`.
+it.skipIf =
+  (condition: unknown) =>
+  (testName: string, test: Test): void => {
+    if (condition) {
+      _strTestRunner
+        ._stackCurrent()
+        .children.push([testName, { tag: "skipped" }]);
+    } else {
+      it(testName, test);
+    }
+  };
+
+// Only runs the test if `condition` is true, and skips it otherwise.
+it.runIf = (condition: unknown) => it.skipIf(!condition);
+
+// Registers a test that is expected to fail, e.g. to document a known bug.
+// It's reported as `XFAIL` when it fails, and fails when it passes.
+it.failing = (testName: string, test: Test): void => {
+  _strTestRunner
+    ._stackCurrent()
+    .children.push([testName, { tag: "it", test, failing: true }]);
+};
+
 export const test = it;
 
-export function xit(testName: string, _test: () => void | Promise<void>): void {
+export function xit(testName: string, _test: Test): void {
   _strTestRunner._stackCurrent().children.push([testName, { tag: "ignored" }]);
 }
 
 export function assertEq<T>(a: T, b: T): void {
   if (a !== b) {
-    console.error(`${a}\n    !==\n${b}`);
-    throw new StrTestFailure();
+    throw new StrTestFailure(`${a}\n    !==\n${b}`);
+  }
+}
+
+export function assertCalled(mock: Mock): void {
+  if (mock.calls.length === 0) {
+    throw new StrTestFailure("expected mock to be called, but it wasn't");
+  }
+}
+
+export function assertCalledTimes(mock: Mock, times: number): void {
+  if (mock.calls.length !== times) {
+    throw new StrTestFailure(
+      `expected mock to be called ${times} time(s), but it was called ${mock.calls.length} time(s)`
+    );
+  }
+}
+
+export function assertCalledWith<F extends (...args: Array<any>) => any>(
+  mock: Mock<F>,
+  ...args: Parameters<F>
+): void {
+  if (!mock.calls.some((call) => isDeepStrictEqual(call, args))) {
+    const calls =
+      mock.calls.length === 0
+        ? "it wasn't called"
+        : "it was called with:\n" +
+          mock.calls.map((call) => `    ${inspect(call)}`).join("\n");
+    throw new StrTestFailure(
+      `expected mock to be called with:\n    ${inspect(args)}\nbut ${calls}`
+    );
   }
 }
@@ -0,0 +1,82 @@
+import { format } from "util";
+
+type Chunk = { label: string; text: string };
+
+export type Capture = {
+  chunks: Array<Chunk>;
+  stop: () => void;
+};
+
+const consoleMethods = ["log", "info", "debug", "warn", "error"] as const;
+
+export function startCapture(): Capture {
+  const chunks: Array<Chunk> = [];
+  const originalConsole = consoleMethods.map((method) => console[method]);
+  for (const method of consoleMethods) {
+    console[method] = (...args: Array<unknown>) => {
+      chunks.push({ label: `console.${method}`, text: format(...args) + "\n" });
+    };
+  }
+  const originalStdoutWrite = process.stdout.write;
+  const originalStderrWrite = process.stderr.write;
+  process.stdout.write = captureWrite(chunks, "stdout");
+  process.stderr.write = captureWrite(chunks, "stderr");
+  return {
+    chunks,
+    stop: () => {
+      consoleMethods.forEach((method, i) => {
+        console[method] = originalConsole[i];
+      });
+      process.stdout.write = originalStdoutWrite;
+      process.stderr.write = originalStderrWrite;
+    },
+  };
+}
+
+function captureWrite(
+  chunks: Array<Chunk>,
+  label: string
+): typeof process.stdout.write {
+  return ((chunk: string | Uint8Array, ...rest: Array<unknown>): boolean => {
+    chunks.push({
+      label,
+      text: typeof chunk === "string" ? chunk : Buffer.from(chunk).toString(),
+    });
+    const callback = rest[rest.length - 1];
+    if (typeof callback === "function") {
+      process.nextTick(callback as () => void);
+    }
+    return true;
+  }) as typeof process.stdout.write;
+}
+
+export function replayCaptured(capture: Capture): void {
+  for (const line of formatCaptured(capture.chunks)) {
+    console.error(line);
+  }
+}
+
+function formatCaptured(chunks: Array<Chunk>): Array<string> {
+  const lines: Array<string> = [];
+  let pending: Chunk | null = null;
+  for (const chunk of chunks) {
+    if (pending !== null && pending.label !== chunk.label) {
+      lines.push(formatLine(pending.label, pending.text));
+      pending = null;
+    }
+    const parts = ((pending?.text ?? "") + chunk.text).split("\n");
+    const last = parts.pop() as string;
+    for (const part of parts) {
+      lines.push(formatLine(chunk.label, part));
+    }
+    pending = last === "" ? null : { label: chunk.label, text: last };
+  }
+  if (pending !== null) {
+    lines.push(formatLine(pending.label, pending.text));
+  }
+  return lines;
+}
+
+function formatLine(label: string, text: string): string {
+  return `    [${label}] ${text}`;
+}
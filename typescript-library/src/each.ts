@@ -0,0 +1,72 @@
+import { format, inspect } from "util";
+
+// The arguments that a row of an `each` table is passed as: arrays are
+// spread, everything else is passed as a single argument.
+export type EachArgs<Row> = Row extends ReadonlyArray<unknown> ? Row : [Row];
+
+// The extra arguments (e.g. the test context) follow the row's arguments.
+// They can't be typed by position for rows that are arrays of varying
+// length, like `number[]`.
+type EachBody<Row, Extra extends Array<unknown>, Result> =
+  number extends EachArgs<Row>["length"]
+    ? (...args: EachArgs<Row>) => Result
+    : (...args: [...EachArgs<Row>, ...Extra]) => Result;
+
+export type Each<Result, Extra extends Array<unknown>> = <Row>(
+  table: ReadonlyArray<Row>
+) => (nameTemplate: string, body: EachBody<Row, Extra, Result>) => void;
+
+// each turns a function that registers a single test (or `describe` block)
+// into one that registers one for every row of a table, e.g.
+// `it.each([[1, 2], [2, 4]])("doubles %d", (input, expected) => ...)`.
+export function each<Result, Extra extends Array<unknown> = []>(
+  register: (name: string, body: (...extra: Extra) => Result) => void
+): Each<Result, Extra> {
+  return (table) => (nameTemplate, body) => {
+    table.forEach((row, index) => {
+      const args: Array<unknown> = Array.isArray(row) ? row : [row];
+      const call = body as unknown as (...args: Array<unknown>) => Result;
+      register(eachName(nameTemplate, row, index), (...extra) =>
+        call(...args, ...extra)
+      );
+    });
+  };
+}
+
+// eachName expands the placeholders in the name of a test that is
+// registered with `each`: `%s`, `%d`, `%i`, `%f`, `%j`, `%o` and `%p` are
+// replaced with the row's arguments in order, `%#` with the row's index and
+// `$field` with the field of a row that's an object.
+export function eachName(
+  nameTemplate: string,
+  row: unknown,
+  index: number
+): string {
+  const args: Array<unknown> = Array.isArray(row) ? [...row] : [row];
+  let name = nameTemplate.replace(/%([sdifjopO#%])/g, (placeholder, kind) => {
+    if (kind === "%") {
+      return "%";
+    }
+    if (kind === "#") {
+      return String(index);
+    }
+    if (args.length === 0) {
+      return placeholder;
+    }
+    const arg = args.shift();
+    return kind === "p" ? inspect(arg) : format(placeholder, arg);
+  });
+  if (typeof row === "object" && row !== null && !Array.isArray(row)) {
+    name = name.replace(/\$([A-Za-z_]\w*(?:\.\w+)*)/g, (placeholder, path) => {
+      let value: unknown = row;
+      for (const key of path.split(".")) {
+        if (typeof value !== "object" || value === null || !(key in value)) {
+          return placeholder;
+        }
+        value = (value as Record<string, unknown>)[key];
+      }
+      return typeof value === "string" ? value : inspect(value);
+    });
+  }
+  return name;
+}
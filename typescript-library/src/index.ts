@@ -1,3 +1,28 @@
 export { StrTestRunner, _strTestRunner } from "./test_tree";
-export { describe, it, xit, test, assertEq } from "./assertions";
-export { beforeEach, afterEach, beforeAll, afterAll } from "./test_setup";
+export {
+  describe,
+  it,
+  xit,
+  test,
+  assertEq,
+  assertCalled,
+  assertCalledTimes,
+  assertCalledWith,
+} from "./assertions";
+export { TestContext } from "./test_context";
+export { mockFn, mockModule, spyOn, Mock, MockResult } from "./mocks";
+export { property, gen, Arbitrary, PropertyOptions } from "./property";
+export {
+  useFakeTimers,
+  useRealTimers,
+  advanceTimersBy,
+  runAllTimers,
+  setSystemTime,
+} from "./timers";
+export {
+  beforeEach,
+  afterEach,
+  beforeAll,
+  afterAll,
+  globalSetupData,
+} from "./test_setup";
@@ -1,10 +1,30 @@
+import { performance } from "perf_hooks";
+import { inspect } from "util";
 import { exhaustivenessCheck } from "./utils";
 import { Context } from "./test_tree";
 
-export type LogKind = "start" | "passed" | "failed" | "ignored";
+export type LogKind =
+  | "start"
+  | "passed"
+  | "failed"
+  | "ignored"
+  | "skipped"
+  | "todo"
+  | "xfail";
 
-export function log(stack: Array<{ description: string }>, kind: LogKind) {
-  const description = stack.map((x) => x.description).join(" -> ");
+export function testPath(stack: Array<{ description: string }>): string {
+  return stack.map((x) => x.description).join(" -> ");
+}
+
+export type Duration = { ms: number; slow: boolean };
+
+export function log(
+  stack: Array<{ description: string }>,
+  kind: LogKind,
+  duration?: Duration,
+  reason?: string
+) {
+  const description = testPath(stack);
   let kindSnippet;
   let color = (s: string) => s;
   switch (kind) {
@@ -28,16 +48,51 @@ export function log(stack: Array<{ description: string }>, kind: LogKind) {
       kindSnippet = "IGNORED";
       break;
     }
+    case "skipped": {
+      color = yellow;
+      kindSnippet = "SKIPPED";
+      break;
+    }
+    case "todo": {
+      color = cyan;
+      kindSnippet = "TODO";
+      break;
+    }
+    case "xfail": {
+      color = yellow;
+      kindSnippet = "XFAIL";
+      break;
+    }
     default: {
       exhaustivenessCheck(kind);
       break;
     }
   }
-  console.error(color(`${description} ${kindSnippet}`));
+  if (reason !== undefined) {
+    kindSnippet += ` (${reason})`;
+  }
+  let line = color(`${description} ${kindSnippet}`);
+  if (duration) {
+    line += ` ${formatDuration(duration)}`;
+  }
+  console.error(line);
+}
+
+// Logs the notes that a test attached with `note`, below its result.
+export function logNotes(notes: Array<[string, unknown]>) {
+  for (const [key, value] of notes) {
+    const formatted = typeof value === "string" ? value : inspect(value);
+    console.error(`    ${key}: ${formatted}`);
+  }
+}
+
+function formatDuration(duration: Duration): string {
+  const formatted = `(${Math.round(duration.ms)}ms)`;
+  return duration.slow ? yellow(formatted) : formatted;
 }
 
 export function logSummary(context: Context) {
-  const numberOfTests = context.passes + context.failures;
+  const numberOfTests = context.passes + context.failures + context.xfails;
   const noun = numberOfTests == 1 ? "test" : "tests";
   let message = `Ran ${numberOfTests} ${noun}, `;
   message += green(`${context.passes} passed`);
@@ -47,13 +102,57 @@ export function logSummary(context: Context) {
     failures = red(failures);
   }
   message += failures;
+  if (context.xfails > 0) {
+    message += `, ${yellow(`${context.xfails} xfail`)}`;
+  }
   if (context.ignored > 0) {
     message += `, ${context.ignored} ignored`;
   }
+  if (context.skipped > 0) {
+    message += `, ${yellow(`${context.skipped} skipped`)}`;
+  }
+  if (context.todos > 0) {
+    message += `, ${cyan(`${context.todos} todo`)}`;
+  }
   message += `.`;
+  message += ` (${Math.round(performance.now())}ms)`;
   console.error(message);
 }
 
+export function logSlowTests(context: Context) {
+  const slowTests = context.durations
+    .filter((test) => test.duration.slow)
+    .sort((a, b) => b.duration.ms - a.duration.ms)
+    .slice(0, 10);
+  if (slowTests.length === 0) {
+    return;
+  }
+  console.error("");
+  console.error("Slowest tests:");
+  for (const test of slowTests) {
+    console.error(`    ${formatDuration(test.duration)} ${test.testPath}`);
+  }
+}
+
+export function logFailures(context: Context) {
+  if (context.failed.length === 0) {
+    return;
+  }
+  console.error("");
+  console.error("Failures:");
+  for (const failure of context.failed) {
+    console.error("");
+    console.error(red(failure.testPath));
+    for (const line of failure.message.split("\n")) {
+      console.error(`    ${line}`);
+    }
+    if (failure.location !== null) {
+      console.error(`    at ${failure.location}`);
+    }
+  }
+}
+
 const green = (s: string): string => `\x1b[32m${s}\x1b[0m`;
 const red = (s: string): string => `\x1b[31m${s}\x1b[0m`;
 const yellow = (s: string): string => `\x1b[33m${s}\x1b[0m`;
+const cyan = (s: string): string => `\x1b[36m${s}\x1b[0m`;
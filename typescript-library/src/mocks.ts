@@ -0,0 +1,97 @@
+import { TestContext } from "./test_context";
+import { Test } from "./test_tree";
+
+type AnyFunction = (...args: Array<any>) => any;
+
+export type MockResult<T> =
+  | { type: "return"; value: T }
+  | { type: "throw"; error: unknown };
+
+export type Mock<F extends AnyFunction = AnyFunction> = F & {
+  calls: Array<Parameters<F>>;
+  results: Array<MockResult<ReturnType<F>>>;
+  mockImplementation: (implementation: F) => Mock<F>;
+  mockReturnValue: (value: ReturnType<F>) => Mock<F>;
+  mockResolvedValue: (value: Awaited<ReturnType<F>>) => Mock<F>;
+  mockRestore: () => void;
+};
+
+export function mockFn<F extends AnyFunction = AnyFunction>(
+  implementation?: F
+): Mock<F> {
+  let current: AnyFunction | undefined = implementation;
+  const mock = function (this: unknown, ...args: Parameters<F>) {
+    mock.calls.push(args);
+    try {
+      const value = current?.apply(this, args);
+      mock.results.push({ type: "return", value });
+      return value;
+    } catch (error) {
+      mock.results.push({ type: "throw", error });
+      throw error;
+    }
+  } as unknown as Mock<F>;
+  mock.calls = [];
+  mock.results = [];
+  mock.mockImplementation = (implementation: F) => {
+    current = implementation;
+    return mock;
+  };
+  mock.mockReturnValue = (value: ReturnType<F>) => {
+    current = () => value;
+    return mock;
+  };
+  mock.mockResolvedValue = (value: Awaited<ReturnType<F>>) => {
+    current = () => Promise.resolve(value);
+    return mock;
+  };
+  mock.mockRestore = () => {};
+  return mock;
+}
+
+// Replaces the given module with the return value of `factory`, for the
+// current test file and everything it imports. `str` finds the calls to
+// `mockModule` when bundling the test file, so this doesn't do anything at
+// runtime. The factory is run before the test file, so it can't use any
+// variables from the test file. `mockFn` and `spyOn` are available in it.
+export function mockModule(specifier: string, factory: () => unknown): void {}
+
+type MethodName<T> = {
+  [K in keyof T]: T[K] extends AnyFunction ? K : never;
+}[keyof T];
+
+// Restores the spies that were created in the currently running test.
+let spiesToRestore: Array<() => void> | null = null;
+
+export function spyOn<T, K extends MethodName<T>>(
+  object: T,
+  method: K
+): Mock<T[K] & AnyFunction> {
+  const original = object[method] as T[K] & AnyFunction;
+  const spy = mockFn(original);
+  const restore = () => {
+    object[method] = original;
+  };
+  spy.mockRestore = restore;
+  object[method] = spy as unknown as T[K];
+  spiesToRestore?.push(restore);
+  return spy;
+}
+
+// `restoreSpies` is installed as an `aroundEach` on the root of the test
+// tree, so it wraps every test including its `beforeEach` and `afterEach`
+// hooks.
+export const restoreSpies =
+  (test: Test) =>
+  async (context: TestContext): Promise<void> => {
+    const spies: Array<() => void> = [];
+    spiesToRestore = spies;
+    try {
+      await test(context);
+    } finally {
+      spiesToRestore = null;
+      for (const restore of spies.reverse()) {
+        restore();
+      }
+    }
+  };
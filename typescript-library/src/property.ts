@@ -0,0 +1,305 @@
+import { randomBytes } from "crypto";
+import { inspect } from "util";
+import { it } from "./assertions";
+import { _strTestRunner, StrTestFailure } from "./test_tree";
+
+// A generated value together with the smaller values it can be shrunk to.
+// Shrinks are computed lazily, since only the shrinks of failing values are
+// ever looked at.
+type Shrinkable<T> = {
+  value: T;
+  shrinks: () => Iterable<Shrinkable<T>>;
+};
+
+// A small seedable random number generator (mulberry32), so that failing
+// runs can be reproduced with `--seed`.
+export class Random {
+  private state: number;
+
+  constructor(seed: number) {
+    this.state = seed >>> 0;
+  }
+
+  next(): number {
+    this.state = (this.state + 0x6d2b79f5) >>> 0;
+    let t = this.state;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  }
+
+  // Returns an integer between `min` and `max` (inclusive).
+  int(min: number, max: number): number {
+    return min + Math.floor(this.next() * (max - min + 1));
+  }
+}
+
+export class Arbitrary<T> {
+  constructor(readonly generate: (random: Random) => Shrinkable<T>) {}
+
+  map<U>(f: (value: T) => U): Arbitrary<U> {
+    return new Arbitrary((random) => mapShrinkable(this.generate(random), f));
+  }
+
+  // Only generates values that satisfy `predicate`. The predicate shouldn't
+  // reject too many values, since generating gives up after 1000 tries.
+  filter(predicate: (value: T) => boolean): Arbitrary<T> {
+    return new Arbitrary((random) => {
+      for (let i = 0; i < 1000; i++) {
+        const shrinkable = this.generate(random);
+        if (predicate(shrinkable.value)) {
+          return filterShrinkable(shrinkable, predicate);
+        }
+      }
+      throw new Error("filter: couldn't generate a value in 1000 tries");
+    });
+  }
+}
+
+function mapShrinkable<T, U>(
+  shrinkable: Shrinkable<T>,
+  f: (value: T) => U
+): Shrinkable<U> {
+  return {
+    value: f(shrinkable.value),
+    shrinks: function* () {
+      for (const shrink of shrinkable.shrinks()) {
+        yield mapShrinkable(shrink, f);
+      }
+    },
+  };
+}
+
+function filterShrinkable<T>(
+  shrinkable: Shrinkable<T>,
+  predicate: (value: T) => boolean
+): Shrinkable<T> {
+  return {
+    value: shrinkable.value,
+    shrinks: function* () {
+      for (const shrink of shrinkable.shrinks()) {
+        if (predicate(shrink.value)) {
+          yield filterShrinkable(shrink, predicate);
+        }
+      }
+    },
+  };
+}
+
+// Integers shrink towards the value closest to zero in their range.
+function intShrinkable(value: number, target: number): Shrinkable<number> {
+  return {
+    value,
+    shrinks: function* () {
+      if (value === target) {
+        return;
+      }
+      yield intShrinkable(target, target);
+      for (
+        let distance = Math.trunc((value - target) / 2);
+        distance !== 0;
+        distance = Math.trunc(distance / 2)
+      ) {
+        yield intShrinkable(value - distance, target);
+      }
+    },
+  };
+}
+
+// Arrays shrink by removing elements (as many as possible first) and then by
+// shrinking single elements.
+function arrayShrinkable<T>(
+  elements: Array<Shrinkable<T>>,
+  minLength: number
+): Shrinkable<Array<T>> {
+  return {
+    value: elements.map((element) => element.value),
+    shrinks: function* () {
+      if (elements.length > minLength) {
+        yield arrayShrinkable(elements.slice(0, minLength), minLength);
+        for (let i = 0; i < elements.length; i++) {
+          yield arrayShrinkable(
+            [...elements.slice(0, i), ...elements.slice(i + 1)],
+            minLength
+          );
+        }
+      }
+      yield* shrinkElements(elements, (shrunk) =>
+        arrayShrinkable(shrunk, minLength)
+      );
+    },
+  };
+}
+
+// Tuples keep their length and only shrink their elements.
+function tupleShrinkable(
+  elements: Array<Shrinkable<unknown>>
+): Shrinkable<Array<unknown>> {
+  return {
+    value: elements.map((element) => element.value),
+    shrinks: () => shrinkElements(elements, tupleShrinkable),
+  };
+}
+
+function* shrinkElements<T, R>(
+  elements: Array<Shrinkable<T>>,
+  rebuild: (elements: Array<Shrinkable<T>>) => Shrinkable<R>
+): Iterable<Shrinkable<R>> {
+  for (let i = 0; i < elements.length; i++) {
+    for (const shrink of elements[i].shrinks()) {
+      yield rebuild([
+        ...elements.slice(0, i),
+        shrink,
+        ...elements.slice(i + 1),
+      ]);
+    }
+  }
+}
+
+// Generates integers between `min` and `max` (inclusive).
+function int(min: number = -1000, max: number = 1000): Arbitrary<number> {
+  const target = Math.min(Math.max(0, min), max);
+  return new Arbitrary((random) =>
+    intShrinkable(random.int(min, max), target)
+  );
+}
+
+type LengthOptions = { minLength?: number; maxLength?: number };
+
+function array<T>(
+  element: Arbitrary<T>,
+  { minLength = 0, maxLength = 10 }: LengthOptions = {}
+): Arbitrary<Array<T>> {
+  return new Arbitrary((random) => {
+    const length = random.int(minLength, maxLength);
+    const elements: Array<Shrinkable<T>> = [];
+    for (let i = 0; i < length; i++) {
+      elements.push(element.generate(random));
+    }
+    return arrayShrinkable(elements, minLength);
+  });
+}
+
+// Characters shrink towards the start of the alphabet, i.e. towards "a".
+const alphabet =
+  "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+function string(options: LengthOptions = {}): Arbitrary<string> {
+  const char = int(0, alphabet.length - 1).map((i) => alphabet[i]);
+  return array(char, options).map((chars) => chars.join(""));
+}
+
+function record<T extends Record<string, unknown>>(shape: {
+  [K in keyof T]: Arbitrary<T[K]>;
+}): Arbitrary<T> {
+  const keys = Object.keys(shape) as Array<keyof T>;
+  return new Arbitrary((random) =>
+    tupleShrinkable(keys.map((key) => shape[key].generate(random)))
+  ).map((values) => {
+    const result = {} as T;
+    keys.forEach((key, i) => {
+      result[key] = values[i] as T[typeof key];
+    });
+    return result;
+  });
+}
+
+// Generates values from one of the given arbitraries, picked at random.
+function oneOf<T>(...arbitraries: Array<Arbitrary<T>>): Arbitrary<T> {
+  return new Arbitrary((random) =>
+    arbitraries[random.int(0, arbitraries.length - 1)].generate(random)
+  );
+}
+
+export const gen = { int, string, array, record, oneOf };
+
+export type PropertyOptions = { runs?: number; seed?: number };
+
+const maxShrinks = 1000;
+
+// Registers a test that checks `predicate` for `runs` (default: 100) sets
+// of generated arguments. The predicate fails by returning `false` or by
+// throwing. Failing arguments are shrunk to a minimal counterexample.
+export function property<Args extends Array<unknown> | []>(
+  name: string,
+  arbitraries: { [K in keyof Args]: Arbitrary<Args[K]> },
+  predicate: (...args: Args) => boolean | void | Promise<boolean | void>,
+  options: PropertyOptions = {}
+): void {
+  const check = (args: Array<unknown>) =>
+    checkPredicate(
+      predicate as unknown as (...args: Array<unknown>) => unknown,
+      args
+    );
+  it(name, async () => {
+    const seed =
+      options.seed ??
+      _strTestRunner._options.seed ??
+      randomBytes(4).readUInt32LE(0);
+    const random = new Random(seed);
+    const runs = options.runs ?? 100;
+    for (let run = 1; run <= runs; run++) {
+      let current = tupleShrinkable(
+        (arbitraries as unknown as Array<Arbitrary<unknown>>).map(
+          (arbitrary) => arbitrary.generate(random)
+        )
+      );
+      let error = await check(current.value);
+      if (error === null) {
+        continue;
+      }
+      let shrinks = 0;
+      shrinking: while (shrinks < maxShrinks) {
+        for (const shrink of current.shrinks()) {
+          const shrinkError = await check(shrink.value);
+          if (shrinkError !== null) {
+            current = shrink;
+            error = shrinkError;
+            shrinks++;
+            continue shrinking;
+          }
+        }
+        break;
+      }
+      throw propertyFailure(run, seed, shrinks, current.value, error);
+    }
+  });
+}
+
+async function checkPredicate(
+  predicate: (...args: Array<unknown>) => unknown,
+  args: Array<unknown>
+): Promise<{ exception: unknown } | null> {
+  try {
+    if ((await predicate(...args)) === false) {
+      return { exception: new StrTestFailure("predicate returned false") };
+    }
+    return null;
+  } catch (exception) {
+    return { exception };
+  }
+}
+
+function propertyFailure(
+  run: number,
+  seed: number,
+  shrinks: number,
+  args: Array<unknown>,
+  error: { exception: unknown }
+): StrTestFailure {
+  const message =
+    error.exception instanceof StrTestFailure
+      ? error.exception.message
+      : `EXCEPTION: ${error.exception}`;
+  const failure = new StrTestFailure(
+    `property failed after ${run} run(s) (rerun with --seed ${seed})\n` +
+      `counterexample (shrunk ${shrinks} time(s)): ${inspect(args)}\n` +
+      message
+  );
+  // Keep the stack of the original exception, so that the failure is
+  // reported at the location where the predicate failed.
+  if (error.exception instanceof Error && error.exception.stack) {
+    failure.stack = error.exception.stack;
+  }
+  return failure;
+}
@@ -0,0 +1,173 @@
+import * as fs from "fs";
+import * as path from "path";
+import { inspect } from "util";
+import { Context, TestResult } from "./test_tree";
+
+export function writeReports(context: Context): void {
+  const reporters = context.options.reporters ?? {};
+  if (reporters.json !== undefined) {
+    writeReport(reporters.json, jsonReport(context));
+  }
+  if (reporters.junit !== undefined) {
+    writeReport(reporters.junit, junitReport(context));
+  }
+}
+
+// Converts the notes that a test attached with `note` for the reports (and
+// for sending them from worker processes). Values that can't be
+// represented in JSON are formatted like in the output.
+export function reportedNotes(
+  notes: Array<[string, unknown]>
+): { [key: string]: unknown } | undefined {
+  if (notes.length === 0) {
+    return undefined;
+  }
+  const result: { [key: string]: unknown } = {};
+  for (const [key, value] of notes) {
+    try {
+      result[key] = JSON.parse(JSON.stringify(value));
+    } catch {
+      result[key] = inspect(value);
+    }
+  }
+  return result;
+}
+
+function writeReport(file: string, content: string): void {
+  fs.mkdirSync(path.dirname(file), { recursive: true });
+  fs.writeFileSync(file, content);
+}
+
+function jsonReport(context: Context): string {
+  const report = {
+    summary: {
+      tests: context.passes + context.failures + context.xfails,
+      passed: context.passes,
+      failed: context.failures,
+      xfail: context.xfails,
+      ignored: context.ignored,
+      skipped: context.skipped,
+      todo: context.todos,
+    },
+    tests: context.results.map((result) => ({
+      name: result.path.join(" -> "),
+      ...result,
+      duration:
+        result.duration === undefined ? undefined : Math.round(result.duration),
+    })),
+  };
+  return JSON.stringify(report, null, 2) + "\n";
+}
+
+// Writes one `testsuite` per test file. Ignored, skipped and todo tests are
+// reported as skipped.
+function junitReport(context: Context): string {
+  const suites = new Map<string, Array<TestResult>>();
+  for (const result of context.results) {
+    const file = result.path[0];
+    suites.set(file, [...(suites.get(file) ?? []), result]);
+  }
+  const lines = ['<?xml version="1.0" encoding="UTF-8"?>'];
+  lines.push(
+    `<testsuites${attributes({
+      name: "str",
+      ...counts(context.results),
+      time: seconds(context.results),
+    })}>`
+  );
+  for (const [file, results] of suites) {
+    lines.push(
+      `  <testsuite${attributes({
+        name: file,
+        ...counts(results),
+        time: seconds(results),
+      })}>`
+    );
+    for (const result of results) {
+      const children: Array<string> = [];
+      if (result.notes !== undefined) {
+        children.push("      <properties>");
+        for (const [name, value] of Object.entries(result.notes)) {
+          children.push(
+            `        <property${attributes({
+              name,
+              value: typeof value === "string" ? value : JSON.stringify(value),
+            })}/>`
+          );
+        }
+        children.push("      </properties>");
+      }
+      switch (result.status) {
+        case "failed": {
+          let text = result.message ?? "";
+          if (result.location !== undefined) {
+            text += `\n    at ${result.location}`;
+          }
+          children.push(
+            `      <failure${attributes({
+              message: result.reason ?? text.split("\n")[0],
+            })}>${escape(text)}</failure>`
+          );
+          break;
+        }
+        case "ignored":
+        case "skipped":
+        case "todo": {
+          children.push(
+            `      <skipped${attributes({
+              message: result.reason ?? result.status,
+            })}/>`
+          );
+          break;
+        }
+      }
+      const testcase = `    <testcase${attributes({
+        classname: file,
+        name: result.path.slice(1).join(" -> "),
+        time: seconds([result]),
+      })}`;
+      if (children.length === 0) {
+        lines.push(`${testcase}/>`);
+      } else {
+        lines.push(`${testcase}>`, ...children, "    </testcase>");
+      }
+    }
+    lines.push("  </testsuite>");
+  }
+  lines.push("</testsuites>");
+  return lines.join("\n") + "\n";
+}
+
+function counts(results: Array<TestResult>): {
+  tests: number;
+  failures: number;
+  skipped: number;
+} {
+  const count = (statuses: Array<TestResult["status"]>) =>
+    results.filter((result) => statuses.includes(result.status)).length;
+  return {
+    tests: results.length,
+    failures: count(["failed"]),
+    skipped: count(["ignored", "skipped", "todo"]),
+  };
+}
+
+function seconds(results: Array<TestResult>): string {
+  const ms = results.reduce((sum, result) => sum + (result.duration ?? 0), 0);
+  return (ms / 1000).toFixed(3);
+}
+
+function attributes(values: { [name: string]: string | number }): string {
+  return Object.entries(values)
+    .map(([name, value]) => ` ${name}="${escape(String(value))}"`)
+    .join("");
+}
+
+function escape(text: string): string {
+  return text
+    .replace(/&/g, "&amp;")
+    .replace(/</g, "&lt;")
+    .replace(/>/g, "&gt;")
+    .replace(/"/g, "&quot;")
+    .replace(/'/g, "&apos;");
+}
@@ -0,0 +1,39 @@
+import * as path from "path";
+import { fileURLToPath } from "url";
+
+// Returns the location of the innermost stack frame of the given exception
+// that is in user code, i.e. not in `str` itself, in `node_modules`, in node
+// internals or in the generated test runner code. The location is relative
+// to `root`, which defaults to the current directory.
+export function sourceLocation(
+  exception: unknown,
+  root: string = process.cwd()
+): string | null {
+  if (!(exception instanceof Error) || typeof exception.stack !== "string") {
+    return null;
+  }
+  for (const line of exception.stack.split("\n")) {
+    const match = line.match(/^\s+at (?:.*\()?(.+?):(\d+):(\d+)\)?$/);
+    if (match === null) {
+      continue;
+    }
+    let file = match[1];
+    if (file.startsWith("file://")) {
+      file = fileURLToPath(file);
+    }
+    if (isUserCode(file)) {
+      return `${path.relative(root, file)}:${match[2]}:${match[3]}`;
+    }
+  }
+  return null;
+}
+
+function isUserCode(file: string): boolean {
+  return (
+    path.isAbsolute(file) &&
+    !file.startsWith(__dirname + path.sep) &&
+    file !== process.argv[1] &&
+    !path.basename(file).startsWith("<") &&
+    !file.split(path.sep).includes("node_modules")
+  );
+}
@@ -0,0 +1,94 @@
+import * as fs from "fs";
+import * as os from "os";
+import * as path from "path";
+
+// The context that is passed to tests and to `beforeEach` and `afterEach`
+// hooks.
+export type TestContext = {
+  // The name of the test itself, e.g. `works`.
+  name: string;
+  // The test file and the descriptions of the enclosing `describe` blocks,
+  // followed by the test's name.
+  path: Array<string>;
+  // The path joined like in the output, e.g.
+  // `index.test.ts -> my app -> works`.
+  fullName: string;
+  // Aborted when the test times out.
+  signal: AbortSignal;
+  // A temporary directory that is created on first use and removed after
+  // the test.
+  tempDir: string;
+  // Stops the test and reports it as skipped.
+  skip: (reason?: string) => never;
+  // Registers a function that is run after the test (including its
+  // `afterEach` hooks), in reverse order of registration.
+  onCleanup: (cleanup: () => void | Promise<void>) => void;
+  // Attaches a note to the test, that is reported with the test's result.
+  note: (key: string, value: unknown) => void;
+};
+
+export class TestSkipped extends Error {
+  constructor(readonly reason: string | undefined) {
+    super(reason === undefined ? "test skipped" : `test skipped: ${reason}`);
+    this.name = "TestSkipped";
+  }
+}
+
+// The runner's side of a test context.
+export type TestContextHandle = {
+  context: TestContext;
+  abort: () => void;
+  notes: Array<[string, unknown]>;
+  cleanup: () => Promise<void>;
+};
+
+export function newTestContext(testPath: Array<string>): TestContextHandle {
+  const controller = new AbortController();
+  const cleanups: Array<() => void | Promise<void>> = [];
+  const notes: Array<[string, unknown]> = [];
+  let tempDir: string | null = null;
+  const context: TestContext = {
+    name: testPath[testPath.length - 1],
+    path: testPath,
+    fullName: testPath.join(" -> "),
+    signal: controller.signal,
+    get tempDir(): string {
+      if (tempDir === null) {
+        tempDir = fs.mkdtempSync(path.join(os.tmpdir(), "str-test-"));
+      }
+      return tempDir;
+    },
+    skip: (reason?: string): never => {
+      throw new TestSkipped(reason);
+    },
+    onCleanup: (cleanup) => {
+      cleanups.push(cleanup);
+    },
+    note: (key, value) => {
+      notes.push([key, value]);
+    },
+  };
+  return {
+    context,
+    abort: () => controller.abort(),
+    notes,
+    // Runs all cleanup functions, even if some of them fail, and rethrows
+    // the first failure.
+    cleanup: async () => {
+      let failure: { exception: unknown } | null = null;
+      for (const cleanup of cleanups.reverse()) {
+        try {
+          await cleanup();
+        } catch (exception) {
+          failure = failure ?? { exception };
+        }
+      }
+      if (tempDir !== null) {
+        fs.rmSync(tempDir, { recursive: true, force: true });
+      }
+      if (failure !== null) {
+        throw failure.exception;
+      }
+    },
+  };
+}
@@ -1,18 +1,25 @@
 import { _strTestRunner } from ".";
+import { TestContext } from "./test_context";
 
-export function beforeEach(f: () => void | Promise<void>): void {
-  _strTestRunner._stackCurrent().aroundEachs.unshift((test) => async () => {
-    await f();
-    await test();
+export function beforeEach(
+  f: (context: TestContext) => void | Promise<void>
+): void {
+  const aroundEachs = _strTestRunner._stackCurrent().aroundEachs;
+  aroundEachs.unshift((test) => async (context) => {
+    await f(context);
+    await test(context);
   });
 }
 
-export function afterEach(f: () => void | Promise<void>): void {
-  _strTestRunner._stackCurrent().aroundEachs.push((test) => async () => {
+export function afterEach(
+  f: (context: TestContext) => void | Promise<void>
+): void {
+  const aroundEachs = _strTestRunner._stackCurrent().aroundEachs;
+  aroundEachs.push((test) => async (context) => {
     try {
-      await test();
+      await test(context);
     } finally {
-      await f();
+      await f(context);
     }
   });
 }
@@ -24,3 +31,7 @@ export function beforeAll(f: () => void | Promise<void>): void {
 export function afterAll(f: () => void | Promise<void>): void {
   _strTestRunner._stackCurrent().afterAlls.push(f);
 }
+
+export function globalSetupData<T = unknown>(): T {
+  return _strTestRunner._globalSetupData as T;
+}
@@ -1,27 +1,142 @@
-import { log, logSummary } from "./logging";
+import { startCapture, replayCaptured } from "./capture";
+import { performance } from "perf_hooks";
+import {
+  Duration,
+  log,
+  logFailures,
+  logNotes,
+  logSlowTests,
+  logSummary,
+  testPath,
+} from "./logging";
+import { sourceLocation } from "./source_location";
 import { exhaustivenessCheck } from "./utils";
+import { reportedNotes, writeReports } from "./reporters";
+import {
+  readWorkerAssignment,
+  runWorkers,
+  sendWorkerResults,
+  WorkerAssignment,
+} from "./workers";
+import { restoreSpies } from "./mocks";
+import { realTimers, restoreTimers } from "./timers";
+import {
+  newTestContext,
+  TestContext,
+  TestContextHandle,
+  TestSkipped,
+} from "./test_context";
 
-export class StrTestFailure {}
+export class StrTestFailure extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = "StrTestFailure";
+  }
+}
 
 export type StrTestRunner = {
   _stack: Array<TestTree>;
   _stackCurrent: () => TestTree;
+  _globalSetupResult: unknown;
+  _globalSetupData: unknown;
+  _options: RunOptions;
+  _mode: RunMode;
+  _testFiles: Array<string>;
+  configure: (options: RunOptions) => void;
+  runGlobalSetup: (
+    dynamicImport: () => Promise<GlobalSetupModule>
+  ) => Promise<void>;
+  runSetupFile: (dynamicImport: () => Promise<unknown>) => Promise<void>;
   enterTestFile: (
     testFileName: string,
     dynamicImport: () => Promise<void>
   ) => Promise<void>;
-  runTests: () => Promise<void>;
+  runTests: (
+    globalTeardown?: () => Promise<GlobalTeardownModule>
+  ) => Promise<void>;
+};
+
+type GlobalSetupModule = { default?: () => unknown };
+
+type GlobalTeardownModule = {
+  default?: (globalSetupResult: unknown) => void | Promise<void>;
+};
+
+export type RunOptions = {
+  timeout?: number;
+  capture?: boolean;
+  slow?: number;
+  filter?: string;
+  root?: string;
+  seed?: number;
+  reporters?: { json?: string; junit?: string };
+  parallelism?: number;
 };
 
+// With `parallelism` the process that `str` starts becomes the coordinator:
+// it runs the global setup and teardown, distributes the test files over
+// worker processes and reports their merged results. Only the workers
+// import the setup and test files.
+type RunMode =
+  | { tag: "serial" }
+  | { tag: "coordinator"; parallelism: number }
+  | { tag: "worker"; assignment: WorkerAssignment };
+
 const newStrTestRunner = (): StrTestRunner => {
   let strTestRunner: StrTestRunner;
   strTestRunner = {
-    _stack: [newTestTree()],
+    _stack: [{ ...newTestTree(), aroundEachs: [restoreSpies, restoreTimers] }],
     _stackCurrent: () => strTestRunner._stack[strTestRunner._stack.length - 1],
+    _globalSetupResult: undefined,
+    _globalSetupData: undefined,
+    _options: {},
+    _mode: { tag: "serial" },
+    _testFiles: [],
+    configure: (options: RunOptions) => {
+      strTestRunner._options = options;
+      const assignment = readWorkerAssignment();
+      if (assignment !== null) {
+        strTestRunner._mode = { tag: "worker", assignment };
+      } else if (options.parallelism !== undefined && options.parallelism > 1) {
+        strTestRunner._mode = {
+          tag: "coordinator",
+          parallelism: options.parallelism,
+        };
+      }
+    },
+    runGlobalSetup: async (
+      dynamicImport: () => Promise<GlobalSetupModule>
+    ) => {
+      const mode = strTestRunner._mode;
+      if (mode.tag === "worker") {
+        strTestRunner._globalSetupData = mode.assignment.globalSetupData;
+        return;
+      }
+      const setupModule = await dynamicImport();
+      const result = await setupModule.default?.();
+      strTestRunner._globalSetupResult = result;
+      // Tests only get a serialized copy of the data.
+      strTestRunner._globalSetupData =
+        result === undefined ? undefined : JSON.parse(JSON.stringify(result));
+    },
+    runSetupFile: async (dynamicImport: () => Promise<unknown>) => {
+      if (strTestRunner._mode.tag !== "coordinator") {
+        await dynamicImport();
+      }
+    },
     enterTestFile: async (
       testFileName: string,
       dynamicImport: () => Promise<void>
     ) => {
+      const mode = strTestRunner._mode;
+      strTestRunner._testFiles.push(testFileName);
+      if (
+        mode.tag === "coordinator" ||
+        (mode.tag === "worker" &&
+          !mode.assignment.files.includes(testFileName))
+      ) {
+        return;
+      }
       let child: TestChild = {
         tag: "test file",
         tree: newTestTree(),
@@ -31,19 +146,62 @@ const newStrTestRunner = (): StrTestRunner => {
       await dynamicImport();
       _strTestRunner._stack.pop();
     },
-    runTests: async () => {
-      await runTestTree(strTestRunner._stack[0]);
+    runTests: async (globalTeardown?: () => Promise<GlobalTeardownModule>) => {
+      const options = strTestRunner._options;
+      const mode = strTestRunner._mode;
+      const teardown = async () => {
+        if (globalTeardown) {
+          const teardownModule = await globalTeardown();
+          await teardownModule.default?.(strTestRunner._globalSetupResult);
+        }
+      };
+      switch (mode.tag) {
+        case "serial": {
+          await runTestTree(
+            options,
+            (context) => runTestTreeHelper(context, strTestRunner._stack[0]),
+            teardown
+          );
+          break;
+        }
+        case "coordinator": {
+          await runTestTree(
+            options,
+            (context) =>
+              runWorkers(
+                context,
+                strTestRunner._testFiles,
+                mode.parallelism,
+                strTestRunner._globalSetupData
+              ),
+            teardown
+          );
+          break;
+        }
+        case "worker": {
+          const context = newContext(options);
+          await runTestTreeHelper(context, strTestRunner._stack[0]);
+          sendWorkerResults(context);
+          break;
+        }
+        default: {
+          exhaustivenessCheck(mode);
+          break;
+        }
+      }
     },
   };
   return strTestRunner;
 };
 
-type Test = () => void | Promise<void>;
+export type Test = (context: TestContext) => void | Promise<void>;
+
+type AroundEach = (test: Test) => (context: TestContext) => Promise<void>;
 
 type TestTree = {
   children: Array<[string, TestChild]>;
   beforeEachs: Array<() => void>;
-  aroundEachs: Array<(test: Test) => () => Promise<void>>;
+  aroundEachs: Array<AroundEach>;
   beforeAlls: Array<() => void | Promise<void>>;
   afterAlls: Array<() => void | Promise<void>>;
 };
@@ -57,75 +215,242 @@ export const newTestTree = (): TestTree => ({
 });
 
 export type TestChild =
-  | { tag: "it"; test: Test }
+  | { tag: "it"; test: Test; failing: boolean }
   | { tag: "ignored" }
+  | { tag: "skipped" }
+  | { tag: "todo" }
   | { tag: "describe"; tree: TestTree }
   | { tag: "test file"; tree: TestTree };
 
-async function runTestTree(tree: TestTree) {
-  const context: Context = {
+function newContext(options: RunOptions): Context {
+  return {
+    options,
     passes: 0,
     failures: 0,
     ignored: 0,
+    skipped: 0,
+    todos: 0,
+    xfails: 0,
     stack: [],
+    failed: [],
+    durations: [],
+    results: [],
   };
-  await runTestTreeHelper(context, tree);
+}
+
+async function runTestTree(
+  options: RunOptions,
+  run: (context: Context) => Promise<void>,
+  globalTeardown: () => Promise<void>
+) {
+  const context = newContext(options);
+  try {
+    await run(context);
+  } finally {
+    // Failing `beforeAll` and `afterAll` hooks abort the run, but the global
+    // teardown still has to clean up.
+    await globalTeardown();
+  }
   logSummary(context);
+  logSlowTests(context);
+  logFailures(context);
+  writeReports(context);
   if (context.failures > 0) {
     process.exit(1);
   }
 }
 
 export type Context = {
+  options: RunOptions;
   passes: number;
   failures: number;
   ignored: number;
+  skipped: number;
+  todos: number;
+  xfails: number;
   stack: Array<{
     description: string;
-    aroundEachs: Array<(test: Test) => () => Promise<void>>;
+    aroundEachs: Array<AroundEach>;
   }>;
+  failed: Array<Failure>;
+  durations: Array<{ testPath: string; duration: Duration }>;
+  results: Array<TestResult>;
+};
+
+export type Failure = {
+  testPath: string;
+  message: string;
+  location: string | null;
+};
+
+// The result of a single test, as written to the reports.
+export type TestResult = {
+  path: Array<string>;
+  status: "passed" | "failed" | "xfail" | "ignored" | "skipped" | "todo";
+  duration?: number;
+  message?: string;
+  location?: string;
+  reason?: string;
+  notes?: { [key: string]: unknown };
 };
 
 async function runTestTreeHelper(
   context: Context,
   tree: TestTree
 ): Promise<void> {
-  for (const f of tree.beforeAlls) {
-    await f();
-  }
+  await runHooks(context, tree.beforeAlls);
   for (const [testName, child] of tree.children) {
     context.stack.push({
       description: testName,
       aroundEachs: tree.aroundEachs,
     });
+    const filter = context.options.filter;
+    if (filter !== undefined && !containsMatch(filter, context.stack, child)) {
+      context.stack.pop();
+      continue;
+    }
     switch (child.tag) {
       case "it": {
         log(context.stack, "start");
+        let test = child.test;
+        for (let i = context.stack.length - 1; i >= 0; i--) {
+          const aroundEachs = context.stack[i].aroundEachs;
+          for (const aroundEach of aroundEachs) {
+            test = aroundEach(test);
+          }
+        }
+        const capture = context.options.capture ? startCapture() : null;
+        const testContext = newTestContext(
+          context.stack.map((x) => x.description)
+        );
+        let failure: { exception: unknown } | null = null;
+        const start = performance.now();
+        try {
+          await withTimeout(context.options.timeout, testContext, test);
+        } catch (exception) {
+          failure = { exception };
+        }
         try {
-          let test = child.test;
-          for (let i = context.stack.length - 1; i >= 0; i--) {
-            const aroundEachs = context.stack[i].aroundEachs;
-            for (const aroundEach of aroundEachs) {
-              test = aroundEach(test);
-            }
+          await testContext.cleanup();
+        } catch (exception) {
+          failure = failure ?? { exception };
+        } finally {
+          capture?.stop();
+        }
+        const ms = performance.now() - start;
+        const exception = failure?.exception;
+        const path = context.stack.map((x) => x.description);
+        const notes = reportedNotes(testContext.notes);
+        if (exception instanceof TestSkipped) {
+          context.skipped++;
+          context.results.push({
+            path,
+            status: "skipped",
+            reason: exception.reason,
+            notes,
+          });
+          log(context.stack, "skipped", undefined, exception.reason);
+          logNotes(testContext.notes);
+          break;
+        }
+        const duration = {
+          ms,
+          slow:
+            context.options.slow !== undefined && ms >= context.options.slow,
+        };
+        context.durations.push({ testPath: testPath(context.stack), duration });
+        // Tests registered with `it.failing` are expected to fail.
+        let failureReason: string | undefined;
+        if (child.failing) {
+          if (failure !== null) {
+            context.xfails++;
+            context.results.push({
+              path,
+              status: "xfail",
+              duration: ms,
+              notes,
+            });
+            log(context.stack, "xfail", duration);
+            logNotes(testContext.notes);
+            break;
           }
-          await test();
+          failure = {
+            exception: new StrTestFailure(
+              "expected test to fail, but it passed"
+            ),
+          };
+          failureReason = "unexpectedly passed";
+        }
+        if (failure === null) {
           context.passes++;
-          log(context.stack, "passed");
-        } catch (exception) {
-          if (!(exception instanceof StrTestFailure)) {
-            console.error(`EXCEPTION: ${exception}`);
+          context.results.push({
+            path,
+            status: "passed",
+            duration: ms,
+            notes,
+          });
+          log(context.stack, "passed", duration);
+          logNotes(testContext.notes);
+        } else {
+          if (capture) {
+            replayCaptured(capture);
           }
+          const message =
+            failure.exception instanceof StrTestFailure
+              ? failure.exception.message
+              : `EXCEPTION: ${failure.exception}`;
+          console.error(message);
+          const location = sourceLocation(
+            failure.exception,
+            context.options.root
+          );
+          context.failed.push({
+            testPath: testPath(context.stack),
+            message,
+            location,
+          });
+          context.results.push({
+            path,
+            status: "failed",
+            duration: ms,
+            message,
+            location: location ?? undefined,
+            reason: failureReason,
+            notes,
+          });
           context.failures++;
-          log(context.stack, "failed");
+          log(context.stack, "failed", duration, failureReason);
+          logNotes(testContext.notes);
         }
         break;
       }
       case "ignored": {
         context.ignored++;
+        context.results.push({
+          path: context.stack.map((x) => x.description),
+          status: "ignored",
+        });
         log(context.stack, "ignored");
         break;
       }
+      case "skipped": {
+        context.skipped++;
+        context.results.push({
+          path: context.stack.map((x) => x.description),
+          status: "skipped",
+        });
+        log(context.stack, "skipped");
+        break;
+      }
+      case "todo": {
+        context.todos++;
+        context.results.push({
+          path: context.stack.map((x) => x.description),
+          status: "todo",
+        });
+        log(context.stack, "todo");
+        break;
+      }
       case "describe": {
         await runTestTreeHelper(context, child.tree);
         break;
@@ -141,8 +466,71 @@ async function runTestTreeHelper(
     }
     context.stack.pop();
   }
-  for (const f of tree.afterAlls) {
-    await f();
+  await runHooks(context, tree.afterAlls);
+}
+
+// Runs `beforeAll` or `afterAll` hooks. Their output is captured like the
+// output of tests and replayed when a hook fails, which aborts the run.
+async function runHooks(
+  context: Context,
+  hooks: Array<() => void | Promise<void>>
+): Promise<void> {
+  for (const hook of hooks) {
+    const capture = context.options.capture ? startCapture() : null;
+    try {
+      await hook();
+    } catch (exception) {
+      if (capture) {
+        capture.stop();
+        replayCaptured(capture);
+      }
+      throw exception;
+    }
+    capture?.stop();
+  }
+}
+
+function containsMatch(
+  filter: string,
+  stack: Array<{ description: string }>,
+  child: TestChild
+): boolean {
+  switch (child.tag) {
+    case "it":
+    case "ignored":
+    case "skipped":
+    case "todo":
+      return testPath(stack).includes(filter);
+    case "describe":
+    case "test file":
+      return child.tree.children.some(([description, grandChild]) =>
+        containsMatch(filter, [...stack, { description }], grandChild)
+      );
+    default:
+      exhaustivenessCheck(child);
+      return false;
+  }
+}
+
+async function withTimeout(
+  timeout: number | undefined,
+  testContext: TestContextHandle,
+  test: Test
+): Promise<void> {
+  if (!timeout) {
+    return await test(testContext.context);
+  }
+  let timer: ReturnType<typeof setTimeout> | undefined;
+  const timedOut = new Promise<void>((_, reject) => {
+    timer = realTimers.setTimeout(() => {
+      testContext.abort();
+      reject(new Error(`test timed out after ${timeout}ms`));
+    }, timeout);
+  });
+  try {
+    await Promise.race([test(testContext.context), timedOut]);
+  } finally {
+    realTimers.clearTimeout(timer);
   }
 }
 
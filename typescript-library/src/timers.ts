@@ -0,0 +1,208 @@
+import { TestContext } from "./test_context";
+import { Test } from "./test_tree";
+
+// The real timer functions, captured when `str` is loaded. The test runner
+// uses these for its own timeouts, so they keep working with fake timers.
+export const realTimers = {
+  setTimeout: globalThis.setTimeout,
+  clearTimeout: globalThis.clearTimeout,
+  setInterval: globalThis.setInterval,
+  clearInterval: globalThis.clearInterval,
+  Date: globalThis.Date,
+};
+
+type FakeTimer = {
+  id: number;
+  time: number;
+  interval: number | null;
+  callback: () => void;
+};
+
+// `now` is the time of the fake timers, which only moves forward with
+// `advanceTimersBy` and `runAllTimers`. `Date.now()` returns `now` plus
+// `systemTimeOffset`, which can be changed with `setSystemTime`.
+type Clock = {
+  now: number;
+  systemTimeOffset: number;
+  nextId: number;
+  timers: Array<FakeTimer>;
+};
+
+let clock: Clock | null = null;
+
+// Replaces `setTimeout`, `setInterval` (and their `clear` functions) and
+// `Date` with fakes that are controlled by `advanceTimersBy`,
+// `runAllTimers` and `setSystemTime`. Fake timers are restored after every
+// test.
+export function useFakeTimers(): void {
+  if (clock !== null) {
+    return;
+  }
+  const newClock: Clock = {
+    now: 0,
+    systemTimeOffset: realTimers.Date.now(),
+    nextId: 1,
+    timers: [],
+  };
+  clock = newClock;
+  Object.assign(globalThis, {
+    setTimeout: fakeSetTimer(newClock, false),
+    setInterval: fakeSetTimer(newClock, true),
+    clearTimeout: fakeClearTimer(newClock),
+    clearInterval: fakeClearTimer(newClock),
+    Date: fakeDate(newClock),
+  });
+}
+
+export function useRealTimers(): void {
+  clock = null;
+  Object.assign(globalThis, realTimers);
+}
+
+// Moves the fake time forward by `ms` milliseconds and runs all timers that
+// are due in that time, in order.
+export function advanceTimersBy(ms: number): void {
+  const clock = fakeClock("advanceTimersBy");
+  const end = clock.now + ms;
+  for (;;) {
+    const timer = nextTimer(clock);
+    if (timer === null || timer.time > end) {
+      break;
+    }
+    runTimer(clock, timer);
+  }
+  clock.now = end;
+}
+
+const maxTimers = 10000;
+
+// Runs all pending timers, including the ones that are scheduled while
+// running them, moving the fake time forward accordingly.
+export function runAllTimers(): void {
+  const clock = fakeClock("runAllTimers");
+  for (let i = 0; i < maxTimers; i++) {
+    const timer = nextTimer(clock);
+    if (timer === null) {
+      return;
+    }
+    runTimer(clock, timer);
+  }
+  throw new Error(
+    `runAllTimers: aborting after ${maxTimers} timers, ` +
+      "probably because of an interval or a timer that reschedules itself"
+  );
+}
+
+// Sets the time that `Date` and `Date.now()` return, without running any
+// timers.
+export function setSystemTime(time: number | Date): void {
+  const clock = fakeClock("setSystemTime");
+  clock.systemTimeOffset = Number(time) - clock.now;
+}
+
+function fakeClock(functionName: string): Clock {
+  if (clock === null) {
+    throw new Error(
+      `${functionName}: fake timers are not enabled, call useFakeTimers() first`
+    );
+  }
+  return clock;
+}
+
+function nextTimer(clock: Clock): FakeTimer | null {
+  let result: FakeTimer | null = null;
+  for (const timer of clock.timers) {
+    if (result === null || timer.time < result.time) {
+      result = timer;
+    }
+  }
+  return result;
+}
+
+function runTimer(clock: Clock, timer: FakeTimer): void {
+  clock.now = Math.max(clock.now, timer.time);
+  if (timer.interval === null) {
+    clock.timers = clock.timers.filter((other) => other !== timer);
+  } else {
+    timer.time = clock.now + timer.interval;
+  }
+  timer.callback();
+}
+
+// Fake timers return handles that mimic node's `Timeout` objects, since
+// code often calls e.g. `unref` on them.
+type FakeTimerHandle = {
+  id: number;
+  ref: () => FakeTimerHandle;
+  unref: () => FakeTimerHandle;
+  hasRef: () => boolean;
+  [Symbol.toPrimitive]: () => number;
+};
+
+function fakeSetTimer(clock: Clock, repeat: boolean) {
+  return (
+    callback: (...args: Array<unknown>) => void,
+    ms?: number,
+    ...args: Array<unknown>
+  ): FakeTimerHandle => {
+    const delay = Math.max(1, Number(ms) || 0);
+    const id = clock.nextId++;
+    clock.timers.push({
+      id,
+      time: clock.now + delay,
+      interval: repeat ? delay : null,
+      callback: () => callback(...args),
+    });
+    const handle: FakeTimerHandle = {
+      id,
+      ref: () => handle,
+      unref: () => handle,
+      hasRef: () => true,
+      [Symbol.toPrimitive]: () => id,
+    };
+    return handle;
+  };
+}
+
+function fakeClearTimer(clock: Clock) {
+  return (handle?: FakeTimerHandle | number): void => {
+    if (handle === undefined || handle === null) {
+      return;
+    }
+    const id = typeof handle === "number" ? handle : handle.id;
+    clock.timers = clock.timers.filter((timer) => timer.id !== id);
+  };
+}
+
+function fakeDate(clock: Clock): DateConstructor {
+  const RealDate = realTimers.Date;
+  const systemTime = () => clock.now + clock.systemTimeOffset;
+  class FakeDate extends RealDate {
+    constructor(...args: Array<any>) {
+      if (args.length === 0) {
+        super(systemTime());
+      } else {
+        super(...(args as [any]));
+      }
+    }
+
+    static now(): number {
+      return systemTime();
+    }
+  }
+  return FakeDate as unknown as DateConstructor;
+}
+
+// `restoreTimers` is installed as an `aroundEach` on the root of the test
+// tree, next to `restoreSpies`.
+export const restoreTimers =
+  (test: Test) =>
+  async (context: TestContext): Promise<void> => {
+    try {
+      await test(context);
+    } finally {
+      if (clock !== null) {
+        useRealTimers();
+      }
+    }
+  };
@@ -0,0 +1,128 @@
+import { fork } from "child_process";
+import { Context } from "./test_tree";
+
+// What the coordinator passes to a worker process, in the `STR_WORKER`
+// environment variable.
+export type WorkerAssignment = {
+  files: Array<string>;
+  globalSetupData: unknown;
+};
+
+type WorkerResults = Omit<Context, "options" | "stack">;
+
+// The results of a worker (or an error message if it died before sending
+// them) and everything it wrote to stdout and stderr.
+type WorkerOutcome = {
+  results: WorkerResults | string;
+  output: Array<{ stream: NodeJS.WriteStream; chunk: Buffer }>;
+};
+
+const workerVariable = "STR_WORKER";
+
+export function readWorkerAssignment(): WorkerAssignment | null {
+  const assignment = process.env[workerVariable];
+  if (assignment === undefined) {
+    return null;
+  }
+  // Processes that the tests start are not workers.
+  delete process.env[workerVariable];
+  return JSON.parse(assignment);
+}
+
+// Splits the test files into up to `parallelism` consecutive chunks, runs
+// every chunk in a worker process and merges the workers' results into
+// `context`, in the order of the test files. The output of the workers is
+// buffered and printed in the same order, so that the output of different
+// test files doesn't interleave.
+export async function runWorkers(
+  context: Context,
+  testFiles: Array<string>,
+  parallelism: number,
+  globalSetupData: unknown
+): Promise<void> {
+  const workers = Math.min(parallelism, testFiles.length);
+  const chunks: Array<Array<string>> = [];
+  for (let i = 0; i < workers; i++) {
+    chunks.push(
+      testFiles.slice(
+        Math.floor((i * testFiles.length) / workers),
+        Math.floor(((i + 1) * testFiles.length) / workers)
+      )
+    );
+  }
+  const outcomes = chunks.map((files) => runWorker({ files, globalSetupData }));
+  for (const [i, pending] of outcomes.entries()) {
+    const { results: result, output } = await pending;
+    for (const { stream, chunk } of output) {
+      stream.write(chunk);
+    }
+    if (typeof result === "string") {
+      // There's no telling which tests of a crashed worker ran, so every
+      // test file it ran counts as one failed test.
+      for (const file of chunks[i]) {
+        context.failures++;
+        context.failed.push({
+          testPath: file,
+          message: result,
+          location: null,
+        });
+        context.results.push({
+          path: [file],
+          status: "failed",
+          message: result,
+        });
+      }
+      continue;
+    }
+    context.passes += result.passes;
+    context.failures += result.failures;
+    context.ignored += result.ignored;
+    context.skipped += result.skipped;
+    context.todos += result.todos;
+    context.xfails += result.xfails;
+    context.failed.push(...result.failed);
+    context.durations.push(...result.durations);
+    context.results.push(...result.results);
+  }
+}
+
+// Runs the bundle again in a worker process.
+function runWorker(assignment: WorkerAssignment): Promise<WorkerOutcome> {
+  return new Promise((resolve) => {
+    const worker = fork(process.argv[1], process.argv.slice(2), {
+      execArgv: process.execArgv,
+      env: { ...process.env, [workerVariable]: JSON.stringify(assignment) },
+      stdio: ["inherit", "pipe", "pipe", "ipc"],
+    });
+    const output: WorkerOutcome["output"] = [];
+    worker.stdout?.on("data", (chunk: Buffer) => {
+      output.push({ stream: process.stdout, chunk });
+    });
+    worker.stderr?.on("data", (chunk: Buffer) => {
+      output.push({ stream: process.stderr, chunk });
+    });
+    let results: WorkerResults | null = null;
+    worker.on("message", (message) => {
+      results = message as WorkerResults;
+    });
+    // Unlike "exit", "close" waits until stdout and stderr are read.
+    worker.on("close", (code, signal) => {
+      resolve({
+        results:
+          results ??
+          `worker exited with ${
+            signal === null ? `code ${code}` : `signal ${signal}`
+          } before reporting results`,
+        output,
+      });
+    });
+  });
+}
+
+export function sendWorkerResults(context: Context): void {
+  const { options, stack, ...results } = context;
+  if (process.send === undefined) {
+    throw new Error("str: worker process without an IPC channel");
+  }
+  process.send(results, undefined, undefined, () => process.exit(0));
+}